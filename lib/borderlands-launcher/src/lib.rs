@@ -81,6 +81,10 @@ impl Client {
     ///
     /// # Supported Games
     /// Currently, only the Borderlands 2 game api still is functional.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip(self), fields(url))
+    )]
     pub async fn get_launcher_content(
         &self,
         game: Game,
@@ -101,17 +105,37 @@ impl Client {
             }
         };
 
-        let response = self.client.get(url).send().await?.error_for_status()?;
-        let stream = response
-            .bytes_stream()
-            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error));
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("url", url.as_str());
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+
+        let response = self.client.get(&url).send().await?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            method = "GET",
+            status = response.status().as_u16(),
+            redirected_to = (response.url().as_str() != url).then(|| response.url().as_str()),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "got response"
+        );
+        let response = response.error_for_status()?;
+        let stream = response.bytes_stream().map_err(std::io::Error::other);
         let reader = StreamReader::new(stream);
         let mut decoder = GzipDecoder::new(reader);
 
         let mut buffer = BytesMut::new();
         while decoder.read_buf(&mut buffer).await? != 0 {}
 
-        Ok(AvailableData::decode(buffer)?)
+        let ret = AvailableData::decode(buffer);
+
+        #[cfg(feature = "tracing")]
+        match &ret {
+            Ok(_) => tracing::debug!("decoded launcher content"),
+            Err(error) => tracing::warn!(%error, "failed to decode launcher content"),
+        }
+
+        Ok(ret?)
     }
 }
 
@@ -0,0 +1,545 @@
+//! An in-process stand-in for the SHiFT site, for testing clients without a real account or network.
+//!
+//! It serves the home, sessions, account, profile, password, preferences, rewards,
+//! entitlement offer codes and code redemption endpoints,
+//! following a scripted [`Scenario`].
+
+mod pages;
+mod scenario;
+
+pub use crate::scenario::{CodeBehavior, Scenario};
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::task::JoinHandle;
+
+/// The csrf token of the home page, used to sign in
+const HOME_CSRF_TOKEN: &str = "home-csrf-token";
+
+/// The display name of the account, until it is updated
+const DISPLAY_NAME: &str = "FakeDisplayName";
+
+/// The first name of the account, until it is updated
+const FIRST_NAME: &str = "Fake";
+
+/// The key and label of each preference, all enabled at first
+const PREFERENCES: [(&str, &str); 3] = [
+    ("newsletter", "Gearbox Newsletter"),
+    (
+        "promotions",
+        "News and promotions from Gearbox and partners",
+    ),
+    ("shift_codes", "SHiFT code announcements"),
+];
+
+/// The name of the session cookie
+const SESSION_COOKIE: &str = "_session_id";
+
+const REDEEMED_TEXT: &str = "Your code was successfully redeemed";
+const REDEEM_FAILED_TEXT: &str = "Failed to redeem your SHiFT code";
+const ALREADY_REDEEMED_TEXT: &str = "This SHiFT code has already been redeemed";
+const LAUNCH_GAME_TEXT: &str =
+    "To continue to redeem SHiFT codes, please launch a SHiFT-enabled title first!";
+
+type SharedState = Arc<Mutex<ServerState>>;
+
+/// A running fake SHiFT server. It shuts down when dropped.
+#[derive(Debug)]
+pub struct FakeServer {
+    addr: SocketAddr,
+    state: SharedState,
+    task: JoinHandle<()>,
+}
+
+impl FakeServer {
+    /// Start a server on a free local port.
+    ///
+    /// This must be called inside a Tokio runtime.
+    pub async fn start(scenario: Scenario) -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(ServerState::new(scenario)));
+        let app = Router::new()
+            .route("/home", get(home))
+            .route("/sessions", post(sessions))
+            .route("/account", get(account))
+            .route("/users/{id}", post(update_user))
+            .route("/password/change", get(password_change))
+            .route("/password", post(password))
+            .route(
+                "/account/preferences",
+                get(preferences).post(update_preferences),
+            )
+            .route("/logout", get(logout))
+            .route("/rewards", get(rewards))
+            .route("/entitlement_offer_codes", get(entitlement_offer_codes))
+            .route("/code_redemptions", post(code_redemptions))
+            .route("/code_redemptions/{id}", get(code_redemption))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                record_request,
+            ))
+            .with_state(state.clone());
+
+        let task = tokio::spawn(async move {
+            // The server only stops when the task is aborted.
+            let _ = axum::serve(listener, app).await;
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    /// The base url of the server, ending in a slash
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// The number of requests made to the given path, like `/sessions`
+    pub fn request_count(&self, path: &str) -> usize {
+        let state = self.state.lock().expect("state poisoned");
+        state
+            .requests
+            .iter()
+            .filter(|request| *request == path)
+            .count()
+    }
+
+    /// End every session, as if they expired
+    pub fn expire_sessions(&self) {
+        self.state.lock().expect("state poisoned").sessions.clear();
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The mutable state of a [`FakeServer`]
+#[derive(Debug)]
+struct ServerState {
+    scenario: Scenario,
+    sessions: HashSet<String>,
+    display_name: String,
+    first_name: String,
+    preferences: Vec<Preference>,
+    next_id: u64,
+    alert: Option<&'static str>,
+    redeemed: HashSet<(String, String)>,
+    redemptions: HashMap<String, Redemption>,
+    rate_limited_requests: u32,
+    requests: Vec<String>,
+}
+
+/// A newsletter or notification toggle on the preferences page
+#[derive(Debug)]
+struct Preference {
+    key: &'static str,
+    label: &'static str,
+    enabled: bool,
+}
+
+/// A redemption being checked
+#[derive(Debug)]
+struct Redemption {
+    polls_left: u32,
+    text: &'static str,
+}
+
+impl ServerState {
+    fn new(scenario: Scenario) -> Self {
+        Self {
+            rate_limited_requests: scenario.rate_limited_requests,
+            scenario,
+            sessions: HashSet::new(),
+            display_name: DISPLAY_NAME.into(),
+            first_name: FIRST_NAME.into(),
+            preferences: PREFERENCES
+                .iter()
+                .map(|&(key, label)| Preference {
+                    key,
+                    label,
+                    enabled: true,
+                })
+                .collect(),
+            next_id: 0,
+            alert: None,
+            redeemed: HashSet::new(),
+            redemptions: HashMap::new(),
+            requests: Vec::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("{:08x}", self.next_id)
+    }
+
+    /// Get the session of a request, if it is valid
+    fn session(&self, headers: &HeaderMap) -> Option<String> {
+        let session = headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == SESSION_COOKIE)
+            .map(|(_, value)| value.to_string())?;
+        self.sessions.contains(&session).then_some(session)
+    }
+}
+
+/// The csrf token of a session
+fn csrf_token(session: &str) -> String {
+    format!("csrf-{session}")
+}
+
+/// Check the csrf token sent in the `X-CSRF-Token` header
+fn has_csrf_header(headers: &HeaderMap, session: &str) -> bool {
+    headers
+        .get("X-CSRF-Token")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|token| token == csrf_token(session))
+}
+
+fn redirect_home() -> Response {
+    Redirect::to("/home").into_response()
+}
+
+/// The response to a request with a missing or stale csrf token
+fn invalid_authenticity_token() -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "ActionController::InvalidAuthenticityToken",
+    )
+        .into_response()
+}
+
+async fn record_request(State(state): State<SharedState>, req: Request, next: Next) -> Response {
+    state
+        .lock()
+        .expect("state poisoned")
+        .requests
+        .push(req.uri().path().to_string());
+    next.run(req).await
+}
+
+async fn home() -> Html<String> {
+    Html(pages::home(HOME_CSRF_TOKEN))
+}
+
+async fn sessions(
+    State(state): State<SharedState>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    if form.get("authenticity_token").map(String::as_str) != Some(HOME_CSRF_TOKEN) {
+        return invalid_authenticity_token();
+    }
+
+    let email = form.get("user[email]").map(String::as_str);
+    let password = form.get("user[password]").map(String::as_str);
+    if email != Some(state.scenario.email.as_str())
+        || password != Some(state.scenario.password.as_str())
+    {
+        return Redirect::to("/home?redirect_to=false").into_response();
+    }
+
+    let session = state.next_id();
+    state.sessions.insert(session.clone());
+    let cookie = format!("{SESSION_COOKIE}={session}; path=/; HttpOnly");
+    (
+        [(
+            header::SET_COOKIE,
+            HeaderValue::from_str(&cookie).expect("invalid cookie"),
+        )],
+        Redirect::to("/account"),
+    )
+        .into_response()
+}
+
+async fn account(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    Html(pages::account(
+        &csrf_token(&session),
+        &state.scenario.email,
+        &state.display_name,
+        &state.first_name,
+    ))
+    .into_response()
+}
+
+/// The profile form. The live site submits it with javascript, and what it responds with is unknown,
+/// so this answers with an empty script. A blank display name is rejected without saving anything.
+async fn update_user(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    if form.get("authenticity_token") != Some(&csrf_token(&session))
+        || form.get("_method").map(String::as_str) != Some("patch")
+    {
+        return invalid_authenticity_token();
+    }
+
+    let display_name = form.get("user[display_name]").cloned().unwrap_or_default();
+    let first_name = form.get("user[first_name]").cloned().unwrap_or_default();
+    if !display_name.trim().is_empty() {
+        state.display_name = display_name;
+        state.first_name = first_name;
+    }
+    ([(header::CONTENT_TYPE, "text/javascript")], "").into_response()
+}
+
+async fn password_change(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    Html(pages::password_change(&csrf_token(&session), None)).into_response()
+}
+
+/// The password form. On success it redirects to the account page,
+/// otherwise the form is shown again with an error.
+async fn password(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    if form.get("authenticity_token") != Some(&csrf_token(&session))
+        || form.get("_method").map(String::as_str) != Some("put")
+    {
+        return invalid_authenticity_token();
+    }
+
+    let current_password = form.get("user[current_password]").map(String::as_str);
+    let new_password = form.get("user[password]").cloned().unwrap_or_default();
+    let confirmation = form.get("user[password_confirmation]");
+    let error = if current_password != Some(state.scenario.password.as_str()) {
+        "Current password is invalid"
+    } else if new_password.is_empty() || confirmation != Some(&new_password) {
+        "Password confirmation doesn't match Password"
+    } else {
+        state.scenario.password = new_password;
+        return Redirect::to("/account").into_response();
+    };
+    Html(pages::password_change(&csrf_token(&session), Some(error))).into_response()
+}
+
+async fn preferences(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    Html(pages::preferences(
+        &csrf_token(&session),
+        &state.preferences,
+    ))
+    .into_response()
+}
+
+/// The preferences form. Like Rails, the last value sent for a name wins,
+/// so a checked box overrides its hidden fallback.
+async fn update_preferences(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    let form: HashMap<String, String> = form.into_iter().collect();
+    if form.get("authenticity_token") != Some(&csrf_token(&session))
+        || form.get("_method").map(String::as_str) != Some("patch")
+    {
+        return invalid_authenticity_token();
+    }
+
+    let locked = state.scenario.locked_preference.clone();
+    for preference in state.preferences.iter_mut() {
+        if locked.as_deref() == Some(preference.key) {
+            continue;
+        }
+        let name = format!("user[email_preferences][{}]", preference.key);
+        preference.enabled = form.get(&name).map(String::as_str) == Some("1");
+    }
+    Redirect::to("/account/preferences").into_response()
+}
+
+async fn logout(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    if let Some(session) = state.session(&headers) {
+        state.sessions.remove(&session);
+    }
+    redirect_home()
+}
+
+async fn rewards(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    let alert = state.alert.take();
+    Html(pages::rewards(&csrf_token(&session), alert)).into_response()
+}
+
+async fn entitlement_offer_codes(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    if state.rate_limited_requests > 0 {
+        state.rate_limited_requests -= 1;
+        let mut res = StatusCode::TOO_MANY_REQUESTS.into_response();
+        if let Some(retry_after) = state.scenario.retry_after {
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        return res;
+    }
+
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    if !has_csrf_header(&headers, &session) {
+        return invalid_authenticity_token();
+    }
+
+    let code = query.get("code").cloned().unwrap_or_default();
+    let behavior = state
+        .scenario
+        .codes
+        .get(&code)
+        .copied()
+        .unwrap_or(CodeBehavior::NonExistent);
+    match behavior {
+        CodeBehavior::NonExistent => "This SHiFT code does not exist".into_response(),
+        CodeBehavior::Expired => "This SHiFT code has expired".into_response(),
+        CodeBehavior::Unavailable => "This code is not available for your account".into_response(),
+        CodeBehavior::Unprocessable => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+        _ => Html(pages::reward_forms(&csrf_token(&session), &code)).into_response(),
+    }
+}
+
+async fn code_redemptions(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+    if form.get("authenticity_token") != Some(&csrf_token(&session)) {
+        return invalid_authenticity_token();
+    }
+
+    let code = form
+        .get("archway_code_redemption[code]")
+        .cloned()
+        .unwrap_or_default();
+    let service = form
+        .get("archway_code_redemption[service]")
+        .cloned()
+        .unwrap_or_default();
+    if state.scenario.broken_service.as_ref() == Some(&service) {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    let behavior = state
+        .scenario
+        .codes
+        .get(&code)
+        .copied()
+        .unwrap_or(CodeBehavior::NonExistent);
+
+    let text = match behavior {
+        CodeBehavior::Redeemable if state.redeemed.insert((code, service)) => REDEEMED_TEXT,
+        CodeBehavior::Failed => REDEEM_FAILED_TEXT,
+        CodeBehavior::Redeemable | CodeBehavior::AlreadyRedeemed => {
+            state.alert = Some(ALREADY_REDEEMED_TEXT);
+            return Redirect::to("/rewards").into_response();
+        }
+        CodeBehavior::LaunchGameRequired => {
+            state.alert = Some(LAUNCH_GAME_TEXT);
+            return Redirect::to("/rewards").into_response();
+        }
+        CodeBehavior::NonExistent
+        | CodeBehavior::Expired
+        | CodeBehavior::Unavailable
+        | CodeBehavior::Unprocessable => {
+            state.alert = Some(REDEEM_FAILED_TEXT);
+            return Redirect::to("/rewards").into_response();
+        }
+    };
+
+    let id = state.next_id();
+    let polls_left = state.scenario.in_progress_polls;
+    state
+        .redemptions
+        .insert(id.clone(), Redemption { polls_left, text });
+    Redirect::to(&format!("/code_redemptions/{id}")).into_response()
+}
+
+async fn code_redemption(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let mut state = state.lock().expect("state poisoned");
+    let Some(session) = state.session(&headers) else {
+        return redirect_home();
+    };
+
+    let is_xhr = headers
+        .get("X-Requested-With")
+        .is_some_and(|value| value == "XMLHttpRequest");
+    if !is_xhr {
+        if !state.redemptions.contains_key(&id) {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        return Html(pages::code_redemption(&csrf_token(&session), &id)).into_response();
+    }
+
+    if !has_csrf_header(&headers, &session) {
+        return invalid_authenticity_token();
+    }
+    let Some(redemption) = state.redemptions.get_mut(&id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if redemption.polls_left > 0 {
+        redemption.polls_left -= 1;
+        return Json(serde_json::json!({ "in_progress": true })).into_response();
+    }
+
+    Json(serde_json::json!({
+        "in_progress": false,
+        "text": redemption.text,
+        "url": "/rewards",
+    }))
+    .into_response()
+}
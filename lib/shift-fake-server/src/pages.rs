@@ -0,0 +1,152 @@
+//! Minimal html for the pages the client parses
+
+use crate::Preference;
+
+/// Wrap a body in a page with the given csrf token
+fn page(csrf_token: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>SHiFT</title>
+  <meta name="csrf-param" content="authenticity_token" />
+  <meta name="csrf-token" content="{csrf_token}" />
+</head>
+<body>
+{body}
+</body>
+</html>
+"#
+    )
+}
+
+/// The home page, with the sign in form
+pub(crate) fn home(csrf_token: &str) -> String {
+    page(
+        csrf_token,
+        r#"<form class="new_user" id="new_user" action="/sessions" method="post">
+  <input type="email" name="user[email]" id="user_email" />
+  <input type="password" name="user[password]" id="user_password" />
+  <input type="submit" name="commit" value="SIGN IN" />
+</form>"#,
+    )
+}
+
+/// The account page, with the profile form
+pub(crate) fn account(
+    csrf_token: &str,
+    email: &str,
+    display_name: &str,
+    first_name: &str,
+) -> String {
+    page(
+        csrf_token,
+        &format!(
+            r#"<form class="edit_user" id="edit_user_1" action="/users/1" data-remote="true" method="post">
+  <input type="hidden" name="_method" value="patch" />
+  <p id="current_email">{email}</p>
+  <p id="current_display_name">{display_name}</p>
+  <p id="current_first_name">{first_name}</p>
+</form>"#
+        ),
+    )
+}
+
+/// The password change page, with an optional error
+pub(crate) fn password_change(csrf_token: &str, error: Option<&str>) -> String {
+    let error = error
+        .map(|error| format!("<p>{error}</p>"))
+        .unwrap_or_default();
+    page(
+        csrf_token,
+        &format!(
+            r#"<form class="edit_user" id="edit_user_1" action="/password" method="post">
+  <input name="utf8" type="hidden" value="&#x2713;" />
+  <input type="hidden" name="_method" value="put" />
+  <input type="hidden" name="authenticity_token" value="{csrf_token}" />
+  <div id="error_messages">{error}</div>
+  <input type="password" name="user[current_password]" id="user_current_password" />
+  <input type="password" name="user[password]" id="user_password" />
+  <input type="password" name="user[password_confirmation]" id="user_password_confirmation" />
+  <input type="submit" name="commit" value="Change Password" />
+</form>"#
+        ),
+    )
+}
+
+/// The user preferences page, with a checkbox and a hidden `0` fallback for each preference
+pub(crate) fn preferences(csrf_token: &str, preferences: &[Preference]) -> String {
+    let checkboxes: String = preferences
+        .iter()
+        .map(|preference| {
+            let key = preference.key;
+            let label = preference.label;
+            let checked = if preference.enabled {
+                r#" checked="checked""#
+            } else {
+                ""
+            };
+            format!(
+                r#"  <label for="user_email_preferences_{key}">{label}</label>
+  <input name="user[email_preferences][{key}]" type="hidden" value="0" /><input type="checkbox" value="1"{checked} name="user[email_preferences][{key}]" id="user_email_preferences_{key}" />
+"#
+            )
+        })
+        .collect();
+    page(
+        csrf_token,
+        &format!(
+            r#"<form class="edit_user" id="edit_user_1" action="/account/preferences" method="post">
+  <input name="utf8" type="hidden" value="&#x2713;" />
+  <input type="hidden" name="_method" value="patch" />
+  <input type="hidden" name="authenticity_token" value="{csrf_token}" />
+{checkboxes}  <input type="submit" name="commit" value="Save" />
+</form>"#
+        ),
+    )
+}
+
+/// The rewards page, with an optional alert
+pub(crate) fn rewards(csrf_token: &str, alert: Option<&str>) -> String {
+    let alert = alert
+        .map(|alert| format!(r#"<div class="alert notice"><p>{alert}</p></div>"#))
+        .unwrap_or_default();
+    page(
+        csrf_token,
+        &format!(
+            r#"<div id="flash-messages">{alert}</div>
+<input id="shift_code_input" name="shift_code_input" />
+<button id="shift_code_check" class="sh_button_primary sh_verify_button">Check</button>"#
+        ),
+    )
+}
+
+/// The reward forms for a code, one for each service
+pub(crate) fn reward_forms(csrf_token: &str, code: &str) -> String {
+    [("steam", "Steam"), ("epic", "Epic")]
+        .iter()
+        .map(|(service, name)| {
+            format!(
+                r#"<h2>Borderlands 2</h2>
+<form class="new_archway_code_redemption" action="/code_redemptions" method="post">
+  <input name="utf8" type="hidden" value="&#x2713;" />
+  <input type="hidden" name="authenticity_token" value="{csrf_token}" />
+  <input value="{code}" type="hidden" name="archway_code_redemption[code]" />
+  <input value="check-{code}" type="hidden" name="archway_code_redemption[check]" />
+  <input value="{service}" type="hidden" name="archway_code_redemption[service]" />
+  <input value="willow2" type="hidden" name="archway_code_redemption[title]" />
+  <input type="submit" name="commit" value="Redeem for {name}" />
+</form>
+"#
+            )
+        })
+        .collect()
+}
+
+/// The page shown while a redemption is checked
+pub(crate) fn code_redemption(csrf_token: &str, id: &str) -> String {
+    page(
+        csrf_token,
+        &format!(r#"<div id="check_redemption_status" data-url="/code_redemptions/{id}"></div>"#),
+    )
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+/// How the fake server responds to a code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeBehavior {
+    /// The code can be redeemed once, after which it is already redeemed
+    Redeemable,
+
+    /// The code has expired
+    Expired,
+
+    /// The code does not exist. This is the behavior of every unknown code.
+    NonExistent,
+
+    /// The code is not available for the account
+    Unavailable,
+
+    /// The code was already redeemed
+    AlreadyRedeemed,
+
+    /// A SHiFT-enabled game must be launched before redeeming more codes
+    LaunchGameRequired,
+
+    /// The redemption fails after polling
+    Failed,
+
+    /// Checking the code is answered with a `422 Unprocessable Entity` that is not a csrf rejection
+    Unprocessable,
+}
+
+/// The scripted behavior of a [`FakeServer`](crate::FakeServer)
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// The email of the only account
+    pub email: String,
+
+    /// The password of the only account
+    pub password: String,
+
+    /// The behavior of each code, keyed by the code as it is sent
+    pub codes: HashMap<String, CodeBehavior>,
+
+    /// The number of requests to the entitlement offer codes endpoint answered with 429 before it recovers
+    pub rate_limited_requests: u32,
+
+    /// The `Retry-After` seconds sent with 429 responses
+    pub retry_after: Option<u32>,
+
+    /// The number of status checks answered with "in progress" before each redemption finishes
+    pub in_progress_polls: u32,
+
+    /// A service, like `epic`, whose redemptions are answered with 500
+    pub broken_service: Option<String>,
+
+    /// A preference, like `newsletter`, that keeps its value when the preferences are updated
+    pub locked_preference: Option<String>,
+}
+
+impl Scenario {
+    /// Make a scenario with no codes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the behavior of a code
+    pub fn code(mut self, code: impl Into<String>, behavior: CodeBehavior) -> Self {
+        self.codes.insert(code.into(), behavior);
+        self
+    }
+
+    /// Answer the given number of entitlement offer code requests with 429
+    pub fn rate_limited_requests(mut self, requests: u32) -> Self {
+        self.rate_limited_requests = requests;
+        self
+    }
+
+    /// Answer the given number of status checks with "in progress" before finishing each redemption
+    pub fn in_progress_polls(mut self, polls: u32) -> Self {
+        self.in_progress_polls = polls;
+        self
+    }
+
+    /// Answer every redemption for the given service with 500
+    pub fn broken_service(mut self, service: impl Into<String>) -> Self {
+        self.broken_service = Some(service.into());
+        self
+    }
+
+    /// Keep the value of the given preference when the preferences are updated
+    pub fn locked_preference(mut self, preference: impl Into<String>) -> Self {
+        self.locked_preference = Some(preference.into());
+        self
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            email: "user@example.com".into(),
+            password: "password".into(),
+            codes: HashMap::new(),
+            rate_limited_requests: 0,
+            retry_after: Some(0),
+            in_progress_polls: 0,
+            broken_service: None,
+            locked_preference: None,
+        }
+    }
+}
@@ -1,19 +1,28 @@
 use crate::{Game, OrczResult, ShiftCode};
 use once_cell::sync::Lazy;
 use scraper::{Html, Selector};
+use shift_transport::{Live, Transport};
+use std::sync::Arc;
 use time::Date;
 
 /// Client
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Client {
     client: reqwest::Client,
+    transport: Arc<dyn Transport>,
 }
 
 impl Client {
     /// Make a new [`Client`].
     pub fn new() -> Self {
+        Self::with_transport(Live)
+    }
+
+    /// Make a new [`Client`] that executes every request with the given [`Transport`].
+    pub fn with_transport(transport: impl Transport + 'static) -> Self {
         Client {
             client: reqwest::Client::new(),
+            transport: Arc::new(transport),
         }
     }
 
@@ -21,28 +30,56 @@ impl Client {
     ///
     /// # Errors
     /// Returns an error if the website could not be fetched
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(method = "GET", url))
+    )]
     async fn get_html<F, T>(&self, url: &str, f: F) -> OrczResult<T>
     where
         F: Fn(Html) -> OrczResult<T> + Send + 'static,
         T: Send + 'static,
     {
-        let text = self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .text()
-            .await?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("url", url);
+
+        let req = self.client.get(url).build()?;
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+        let res = self.transport.execute(&self.client, req).await?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            status = res.status().as_u16(),
+            redirected_to = (res.url().as_str() != url).then(|| res.url().as_str()),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "got response"
+        );
+
+        let text = res.error_for_status()?.text().await?;
         tokio::task::spawn_blocking(move || f(Html::parse_document(text.as_str()))).await?
     }
 
     /// Get the shift codes for a given game
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(self)))]
     pub async fn get_shift_codes(&self, game: Game) -> OrczResult<Vec<ShiftCode>> {
-        self.get_html(game.page_url(), move |html| {
-            Ok(extract_shift_codes(&html, game)?)
-        })
-        .await
+        let ret = self
+            .get_html(game.page_url(), move |html| {
+                Ok(extract_shift_codes(&html, game)?)
+            })
+            .await;
+
+        #[cfg(feature = "tracing")]
+        match &ret {
+            Ok(codes) => tracing::debug!(codes = codes.len(), "parsed shift codes"),
+            Err(error) => tracing::warn!(%error, "failed to get shift codes"),
+        }
+
+        ret
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
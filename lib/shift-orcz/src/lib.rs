@@ -21,11 +21,24 @@ pub enum OrczError {
     #[error("invalid table")]
     TableParse(#[from] self::client::ExtractShiftCodesError),
 
+    /// Failed to read or replay a cassette
+    #[error("transport error")]
+    Transport(#[source] shift_transport::TransportError),
+
     /// a tokio task failed
     #[error("tokio task join failed")]
     TokioJoin(#[from] tokio::task::JoinError),
 }
 
+impl From<shift_transport::TransportError> for OrczError {
+    fn from(error: shift_transport::TransportError) -> Self {
+        match error {
+            shift_transport::TransportError::Reqwest(error) => Self::Reqwest(error),
+            error => Self::Transport(error),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,85 @@
+use reqwest::Url;
+use shift_orcz::{Client, Game};
+use shift_transport::{Recorder, Replayer, Transport, TransportFuture};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+const BL2_DOC: &str = include_str!("../test_data/bl2-keys.html");
+
+/// A transport that sends every request to a local server, keeping the path
+#[derive(Debug)]
+struct Local<T> {
+    base_url: Url,
+    inner: T,
+}
+
+impl<T: Transport> Transport for Local<T> {
+    fn execute<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        mut req: reqwest::Request,
+    ) -> TransportFuture<'a> {
+        *req.url_mut() = self.base_url.join(req.url().path()).unwrap();
+        self.inner.execute(client, req)
+    }
+}
+
+/// Serve the given page to every request until the task is aborted
+async fn serve(listener: TcpListener, body: &'static str) {
+    loop {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut buf).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=UTF-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await.unwrap();
+        stream.write_all(body.as_bytes()).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn record_and_replay() {
+    let path =
+        std::env::temp_dir().join(format!("shift-orcz-cassette-{}.json", std::process::id()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let server = tokio::spawn(serve(listener, BL2_DOC));
+
+    let client = Client::with_transport(Local {
+        base_url: base_url.clone(),
+        inner: Recorder::new(&path),
+    });
+    let recorded = client.get_shift_codes(Game::Borderlands2).await.unwrap();
+    server.abort();
+    assert!(!recorded.is_empty());
+
+    let replayer = Replayer::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replayer.remaining(), 1);
+    let client = Client::with_transport(Local {
+        base_url,
+        inner: replayer,
+    });
+    let replayed = client.get_shift_codes(Game::Borderlands2).await.unwrap();
+
+    assert_eq!(replayed.len(), recorded.len());
+    for (replayed, recorded) in replayed.iter().zip(recorded.iter()) {
+        assert_eq!(replayed.rewards, recorded.rewards);
+        assert_eq!(
+            replayed.get_code(0).map(|code| code.as_str()),
+            recorded.get_code(0).map(|code| code.as_str())
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::TransportError;
+use reqwest::ResponseBuilderExt;
+use std::path::Path;
+
+/// A recording of requests and their responses
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Cassette {
+    /// The interactions, in the order they happened
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Load a cassette from a json file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let data = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Save this cassette to a json file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TransportError> {
+        let data = serde_json::to_vec_pretty(self)?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+/// A request and its response
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Interaction {
+    /// The request
+    pub request: RecordedRequest,
+
+    /// The response
+    pub response: RecordedResponse,
+}
+
+/// A recorded request
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RecordedRequest {
+    /// The method, like `GET`
+    pub method: String,
+
+    /// The url
+    pub url: String,
+
+    /// The headers, without cookies or credentials
+    pub headers: Vec<(String, String)>,
+
+    /// The body, if there is one
+    pub body: Option<String>,
+}
+
+/// A recorded response
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RecordedResponse {
+    /// The HTTP status
+    pub status: u16,
+
+    /// The final url, after redirects
+    pub url: String,
+
+    /// The headers, without cookies
+    pub headers: Vec<(String, String)>,
+
+    /// The body
+    pub body: String,
+}
+
+impl RecordedResponse {
+    /// Rebuild a [`reqwest::Response`] from this recording
+    pub(crate) fn to_response(&self) -> Result<reqwest::Response, TransportError> {
+        let url = reqwest::Url::parse(&self.url)
+            .map_err(|_| TransportError::InvalidUrl(self.url.clone()))?;
+
+        let mut builder = http::Response::builder().status(self.status).url(url);
+        for (name, value) in self.headers.iter() {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let res = builder.body(self.body.clone())?;
+        Ok(reqwest::Response::from(res))
+    }
+}
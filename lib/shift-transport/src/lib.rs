@@ -0,0 +1,85 @@
+//! A pluggable HTTP transport shared by the SHiFT clients.
+//!
+//! Besides the [`Live`] transport, a [`Recorder`] saves every request and response to a [`Cassette`] file,
+//! and a [`Replayer`] serves responses from one without touching the network.
+
+mod cassette;
+mod recorder;
+mod replayer;
+
+pub use crate::{
+    cassette::{Cassette, Interaction, RecordedRequest, RecordedResponse},
+    recorder::Recorder,
+    replayer::Replayer,
+};
+use std::{fmt::Debug, future::Future, pin::Pin};
+
+/// The future returned by [`Transport::execute`]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<reqwest::Response, TransportError>> + Send + 'a>>;
+
+/// A way of executing requests
+pub trait Transport: Debug + Send + Sync {
+    /// Execute a request, using the given client if it goes to the network
+    fn execute<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        req: reqwest::Request,
+    ) -> TransportFuture<'a>;
+
+    /// Add a secret, like an account email, that must never be saved.
+    ///
+    /// Transports that do not save anything ignore it.
+    fn add_secret(&self, secret: &str) -> Result<(), TransportError> {
+        let _ = secret;
+        Ok(())
+    }
+}
+
+/// The default transport, which sends every request over the network
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Live;
+
+impl Transport for Live {
+    fn execute<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        req: reqwest::Request,
+    ) -> TransportFuture<'a> {
+        Box::pin(async move { Ok(client.execute(req).await?) })
+    }
+}
+
+/// Transport error
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    /// Reqwest HTTP error
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
+    /// Failed to read or write a cassette
+    #[error("failed to access cassette")]
+    Io(#[from] std::io::Error),
+
+    /// Invalid cassette
+    #[error("invalid cassette")]
+    Json(#[from] serde_json::Error),
+
+    /// A recorded url is invalid
+    #[error("invalid recorded url '{0}'")]
+    InvalidUrl(String),
+
+    /// A recorded response could not be rebuilt
+    #[error("invalid recorded response")]
+    InvalidResponse(#[from] http::Error),
+
+    /// The cassette has no unused response for a request
+    #[error("no recorded response for {method} {url}")]
+    MissingInteraction {
+        /// The request method
+        method: String,
+
+        /// The request url
+        url: String,
+    },
+}
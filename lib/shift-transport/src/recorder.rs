@@ -0,0 +1,324 @@
+use crate::{
+    Cassette, Interaction, RecordedRequest, RecordedResponse, Transport, TransportError,
+    TransportFuture,
+};
+use reqwest::{header, ResponseBuilderExt};
+use std::{path::PathBuf, sync::Mutex};
+
+/// The text secrets are replaced with
+const REDACTED: &str = "[REDACTED]";
+
+/// The shortest secret that is redacted everywhere.
+///
+/// Shorter secrets, like a short first name, are only redacted where they stand alone as a word,
+/// since replacing them inside other words would corrupt the recording.
+const MIN_SECRET_LEN: usize = 4;
+
+/// Request headers that are never recorded
+const SECRET_REQUEST_HEADERS: &[header::HeaderName] = &[
+    header::COOKIE,
+    header::AUTHORIZATION,
+    header::PROXY_AUTHORIZATION,
+];
+
+/// Request headers whose value is redacted
+const REDACTED_REQUEST_HEADERS: &[&str] = &["x-csrf-token"];
+
+/// Form fields whose value is redacted, matched by part of their name
+const SECRET_FIELDS: &[&str] = &[
+    "password",
+    "email",
+    "authenticity_token",
+    "display_name",
+    "first_name",
+];
+
+/// The html just before a csrf token in a page, in the meta tag and in forms
+const CSRF_TOKEN_PREFIXES: &[&str] = &[
+    r#"name="csrf-token" content=""#,
+    r#"name="authenticity_token" value=""#,
+];
+
+/// Response headers that are never recorded.
+///
+/// The encoding and length headers are dropped since the recorded body is already decoded.
+const SKIPPED_RESPONSE_HEADERS: &[header::HeaderName] = &[
+    header::SET_COOKIE,
+    header::CONTENT_ENCODING,
+    header::CONTENT_LENGTH,
+    header::TRANSFER_ENCODING,
+];
+
+/// A transport that sends requests over the network and saves every interaction to a cassette file.
+///
+/// Cookies, credentials, csrf tokens, and password, email and name form fields are redacted,
+/// as well as any extra secrets added with [`Recorder::redact`] or [`Transport::add_secret`].
+/// The SHiFT clients add the account email and names themselves,
+/// but anything else personal in a page, like a linked platform account, must be added by the caller.
+/// The file is rewritten after each interaction.
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    secrets: Mutex<Vec<String>>,
+    cassette: Mutex<Cassette>,
+}
+
+impl Recorder {
+    /// Make a recorder that writes to the given file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            secrets: Mutex::new(Vec::new()),
+            cassette: Mutex::new(Cassette::default()),
+        }
+    }
+
+    /// Redact every occurrence of a secret, like an email.
+    ///
+    /// Secrets shorter than four characters are only redacted where they stand alone as a word.
+    pub fn redact(self, secret: impl Into<String>) -> Self {
+        self.push_secret(secret.into());
+        self
+    }
+
+    /// Add a secret if it is not empty and new, returning whether it was added
+    fn push_secret(&self, secret: String) -> bool {
+        let mut secrets = self.secrets.lock().expect("secrets poisoned");
+        if secret.is_empty() || secrets.contains(&secret) {
+            return false;
+        }
+        secrets.push(secret);
+        true
+    }
+
+    /// Get a copy of the interactions recorded so far
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().expect("cassette poisoned").clone()
+    }
+
+    /// Replace every secret in some text
+    fn redact_text(&self, text: &str) -> String {
+        let secrets = self.secrets.lock().expect("secrets poisoned");
+        secrets.iter().fold(text.to_string(), |text, secret| {
+            if secret.chars().count() < MIN_SECRET_LEN {
+                redact_word(&text, secret)
+            } else {
+                text.replace(secret, REDACTED)
+            }
+        })
+    }
+
+    /// Record a request
+    fn record_request(&self, req: &reqwest::Request) -> RecordedRequest {
+        let headers = req
+            .headers()
+            .iter()
+            .filter(|(name, _)| !SECRET_REQUEST_HEADERS.contains(name))
+            .map(|(name, value)| {
+                if REDACTED_REQUEST_HEADERS.contains(&name.as_str()) {
+                    return (name.to_string(), REDACTED.to_string());
+                }
+                let value = String::from_utf8_lossy(value.as_bytes());
+                (name.to_string(), self.redact_text(&value))
+            })
+            .collect();
+
+        let is_form = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|value| value == "application/x-www-form-urlencoded");
+        let body = req.body().and_then(|body| body.as_bytes()).map(|body| {
+            let body = String::from_utf8_lossy(body);
+            let body = if is_form {
+                redact_secret_fields(&body)
+            } else {
+                body.into_owned()
+            };
+            self.redact_text(&body)
+        });
+
+        RecordedRequest {
+            method: req.method().to_string(),
+            url: self.redact_text(req.url().as_str()),
+            headers,
+            body,
+        }
+    }
+}
+
+impl Transport for Recorder {
+    fn execute<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        req: reqwest::Request,
+    ) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let request = self.record_request(&req);
+
+            let res = client.execute(req).await?;
+            let status = res.status();
+            let url = res.url().clone();
+            let headers: Vec<(header::HeaderName, header::HeaderValue)> = res
+                .headers()
+                .iter()
+                .filter(|(name, _)| !SKIPPED_RESPONSE_HEADERS.contains(name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            let body = res.bytes().await?;
+
+            let response = RecordedResponse {
+                status: status.as_u16(),
+                url: self.redact_text(url.as_str()),
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = String::from_utf8_lossy(value.as_bytes());
+                        (name.to_string(), self.redact_text(&value))
+                    })
+                    .collect(),
+                body: self.redact_text(&redact_csrf_tokens(&String::from_utf8_lossy(&body))),
+            };
+
+            {
+                let mut cassette = self.cassette.lock().expect("cassette poisoned");
+                cassette
+                    .interactions
+                    .push(Interaction { request, response });
+                cassette.save(&self.path)?;
+            }
+
+            // The caller gets the original response, not the redacted recording.
+            let mut builder = http::Response::builder().status(status).url(url);
+            for (name, value) in headers {
+                builder = builder.header(name, value);
+            }
+            Ok(reqwest::Response::from(builder.body(body)?))
+        })
+    }
+
+    /// Add a secret and redact it from the interactions recorded so far too
+    fn add_secret(&self, secret: &str) -> Result<(), TransportError> {
+        if !self.push_secret(secret.to_string()) {
+            return Ok(());
+        }
+
+        let mut cassette = self.cassette.lock().expect("cassette poisoned");
+        if cassette.interactions.is_empty() {
+            return Ok(());
+        }
+        for Interaction { request, response } in cassette.interactions.iter_mut() {
+            request.url = self.redact_text(&request.url);
+            for (_, value) in request
+                .headers
+                .iter_mut()
+                .chain(response.headers.iter_mut())
+            {
+                *value = self.redact_text(value);
+            }
+            if let Some(body) = request.body.as_mut() {
+                *body = self.redact_text(body);
+            }
+            response.url = self.redact_text(&response.url);
+            response.body = self.redact_text(&response.body);
+        }
+        cassette.save(&self.path)
+    }
+}
+
+/// Redact the values of form fields with a [`SECRET_FIELDS`] entry in their name
+fn redact_secret_fields(body: &str) -> String {
+    body.split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _))
+                if SECRET_FIELDS
+                    .iter()
+                    .any(|field| name.to_ascii_lowercase().contains(field)) =>
+            {
+                format!("{name}={REDACTED}")
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Redact a secret only where it is not part of a longer word
+fn redact_word(text: &str, secret: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for (index, _) in text.match_indices(secret) {
+        let end = index + secret.len();
+        let standalone = !text[..index]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+            && !text[end..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric);
+        if standalone && index >= last_end {
+            redacted.push_str(&text[last_end..index]);
+            redacted.push_str(REDACTED);
+            last_end = end;
+        }
+    }
+    redacted.push_str(&text[last_end..]);
+    redacted
+}
+
+/// Redact the csrf tokens in an html page
+fn redact_csrf_tokens(body: &str) -> String {
+    CSRF_TOKEN_PREFIXES
+        .iter()
+        .fold(body.to_string(), |body, prefix| {
+            let mut redacted = String::with_capacity(body.len());
+            let mut rest = body.as_str();
+            while let Some(index) = rest.find(prefix) {
+                let value_start = index + prefix.len();
+                redacted.push_str(&rest[..value_start]);
+                rest = &rest[value_start..];
+                let Some(value_end) = rest.find('"') else {
+                    break;
+                };
+                redacted.push_str(REDACTED);
+                rest = &rest[value_end..];
+            }
+            redacted.push_str(rest);
+            redacted
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn redact_form() {
+        assert_eq!(
+            redact_secret_fields(
+                "authenticity_token=abc%3D&user%5Bemail%5D=a%40b.c&user%5Bpassword%5D=hunter2&commit=SIGN+IN"
+            ),
+            "authenticity_token=[REDACTED]&user%5Bemail%5D=[REDACTED]&user%5Bpassword%5D=[REDACTED]&commit=SIGN+IN"
+        );
+    }
+
+    #[test]
+    fn redact_short_secret() {
+        let recorder = Recorder::new("unused.json").redact("Bob").redact("Al");
+        assert_eq!(
+            recorder
+                .redact_text(r#"<p id="current_first_name">Bob</p> Bobcat, Al's Alarm, user=Al"#),
+            r#"<p id="current_first_name">[REDACTED]</p> Bobcat, [REDACTED]'s Alarm, user=[REDACTED]"#
+        );
+    }
+
+    #[test]
+    fn redact_page() {
+        assert_eq!(
+            redact_csrf_tokens(
+                r#"<meta name="csrf-token" content="abc==" /><input type="hidden" name="authenticity_token" value="def==" />"#
+            ),
+            r#"<meta name="csrf-token" content="[REDACTED]" /><input type="hidden" name="authenticity_token" value="[REDACTED]" />"#
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{Cassette, Interaction, Transport, TransportError, TransportFuture};
+use std::{path::Path, sync::Mutex};
+
+/// A transport that serves responses from a [`Cassette`] and never touches the network.
+///
+/// Each request gets the first unused recorded response with the same method and url.
+/// If there is none, the first unused one with the same method and path is used,
+/// so that urls with redacted parts still match.
+#[derive(Debug)]
+pub struct Replayer {
+    interactions: Mutex<Vec<Option<Interaction>>>,
+}
+
+impl Replayer {
+    /// Make a replayer for a cassette
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            interactions: Mutex::new(cassette.interactions.into_iter().map(Some).collect()),
+        }
+    }
+
+    /// Load a replayer from a cassette file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    /// Get the number of recorded responses that have not been served yet
+    pub fn remaining(&self) -> usize {
+        let interactions = self.interactions.lock().expect("interactions poisoned");
+        interactions.iter().flatten().count()
+    }
+
+    /// Take the recorded interaction for a request
+    fn take(&self, req: &reqwest::Request) -> Option<Interaction> {
+        let mut interactions = self.interactions.lock().expect("interactions poisoned");
+        let method = req.method().as_str();
+        let url = req.url();
+
+        let index = interactions
+            .iter()
+            .position(|interaction| {
+                interaction.as_ref().is_some_and(|interaction| {
+                    interaction.request.method == method && interaction.request.url == url.as_str()
+                })
+            })
+            .or_else(|| {
+                interactions.iter().position(|interaction| {
+                    interaction.as_ref().is_some_and(|interaction| {
+                        interaction.request.method == method
+                            && reqwest::Url::parse(&interaction.request.url)
+                                .is_ok_and(|recorded| recorded.path() == url.path())
+                    })
+                })
+            })?;
+
+        interactions[index].take()
+    }
+}
+
+impl Transport for Replayer {
+    fn execute<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        req: reqwest::Request,
+    ) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let interaction =
+                self.take(&req)
+                    .ok_or_else(|| TransportError::MissingInteraction {
+                        method: req.method().to_string(),
+                        url: req.url().to_string(),
+                    })?;
+            interaction.response.to_response()
+        })
+    }
+}
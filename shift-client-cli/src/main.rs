@@ -2,21 +2,20 @@ mod util;
 
 use crate::util::input;
 use crate::util::input_yn;
-use anyhow::{ensure, Context};
-use reqwest::StatusCode;
-use shift_client::{types::RewardsPage, Client, RewardForm, ShiftError};
+use anyhow::Context;
+use shift_client::{
+    extract_codes,
+    types::{RedemptionOutcome, RedemptionReport},
+    Client, Ledger, ShiftCode, ShiftError,
+};
 use shift_orcz::Game;
 use std::time::Duration;
+use tracing_subscriber::EnvFilter;
 
-async fn manual_loop(client: &Client) {
-    let rewards_page = match client.get_rewards_page().await {
-        Ok(p) => p,
-        Err(error) => {
-            eprintln!("Failed to get rewards page, got error: {error:#?}");
-            return;
-        }
-    };
+/// The ledger file used when `--ledger` is not passed
+const DEFAULT_LEDGER_PATH: &str = "shift-ledger.jsonl";
 
+async fn manual_loop(client: &Client, ledger: &Ledger) {
     loop {
         print!("Enter a shift code, or type 'exit' to exit: ");
         let code = input();
@@ -26,24 +25,44 @@ async fn manual_loop(client: &Client) {
             break;
         }
 
-        if let Err(error) = redeem_code(client, &rewards_page, code.trim()).await {
-            eprintln!("{error:?}");
-            eprintln!();
+        // Accept a single code, or any text containing codes, like a pasted tweet.
+        let codes: Vec<ShiftCode> = match code.parse() {
+            Ok(code) => vec![code],
+            Err(error) => {
+                let codes: Vec<ShiftCode> = extract_codes(&code)
+                    .into_iter()
+                    .map(|extracted| extracted.code)
+                    .collect();
+                if codes.is_empty() {
+                    eprintln!("Invalid code: {error}");
+                    eprintln!();
+                }
+                codes
+            }
+        };
+
+        for code in codes {
+            println!("Redeeming {code}...");
+            match client
+                .redeem_code_with_ledger(&code, ledger)
+                .await
+                .context("Failed to redeem code")
+            {
+                Ok(Some(report)) => print_report(&report),
+                Ok(None) => print_settled(),
+                Err(error) => {
+                    eprintln!("{error:?}");
+                    eprintln!();
+                }
+            }
+            print_ledger_error(ledger);
         }
     }
 }
 
-async fn auto_loop(client: &Client) {
+async fn auto_loop(client: &Client, ledger: &Ledger) {
     let orcz_client = shift_orcz::Client::new();
 
-    let rewards_page = match client.get_rewards_page().await {
-        Ok(p) => p,
-        Err(error) => {
-            eprintln!("Failed to get rewards page, got error: {error:#?}");
-            return;
-        }
-    };
-
     let game = loop {
         println!("What game do you want to target? (bl, bl2, blps, bl3)");
         let choice = input().to_lowercase();
@@ -95,21 +114,38 @@ async fn auto_loop(client: &Client) {
             println!("Source: {}", shift_code.source);
             println!();
 
+            let code: ShiftCode = match code.as_str().parse() {
+                Ok(code) => code,
+                Err(error) => {
+                    eprintln!("Skipping invalid code: {error}");
+                    eprintln!();
+                    continue;
+                }
+            };
+
             println!("Redeeming code...");
             loop {
-                match redeem_code(client, &rewards_page, code.as_str().trim()).await {
-                    Ok(()) => {
+                match client.redeem_code_with_ledger(&code, ledger).await {
+                    Ok(Some(report)) => {
+                        print_report(&report);
+                        break;
+                    }
+                    Ok(None) => {
+                        print_settled();
                         break;
                     }
                     Err(error) => {
-                        if let Some(ShiftError::Reqwest(e)) = error.downcast_ref::<ShiftError>() {
-                            if let Some(StatusCode::TOO_MANY_REQUESTS) = e.status() {
-                                eprintln!("Encountered 429, backing off for 60 seconds...");
-                                tokio::time::sleep(Duration::from_secs(60)).await;
-                                continue;
-                            }
+                        if let ShiftError::RateLimited { retry_after } = &error {
+                            let delay = retry_after.unwrap_or(Duration::from_secs(60));
+                            eprintln!(
+                                "Rate limited, backing off for {} seconds...",
+                                delay.as_secs()
+                            );
+                            tokio::time::sleep(delay).await;
+                            continue;
                         }
 
+                        let error = anyhow::Error::from(error).context("Failed to redeem code");
                         eprintln!("{error:?}");
                         eprintln!();
 
@@ -117,52 +153,95 @@ async fn auto_loop(client: &Client) {
                     }
                 }
             }
+            print_ledger_error(ledger);
             println!();
         }
     }
 }
 
-async fn redeem_code(
-    client: &Client,
-    rewards_page: &RewardsPage,
-    code: &str,
-) -> anyhow::Result<()> {
-    let forms = client
-        .get_reward_forms(rewards_page, code.trim())
-        .await
-        .context("failed to get code")?;
+fn print_report(report: &RedemptionReport) {
+    if let Some(rejection) = report.rejection.as_ref() {
+        eprintln!("Code rejected: {}", describe_outcome(rejection));
+        eprintln!();
+        return;
+    }
 
-    ensure!(!forms.is_empty(), "No forms retrieved for code");
+    if report.entries.is_empty() {
+        eprintln!("No forms retrieved for code");
+        eprintln!();
+        return;
+    }
 
-    for form in forms {
-        match redeem_form(client, &form).await {
-            Ok(()) => {}
-            Err(error) => {
-                eprintln!("{error:?}");
-                eprintln!();
-            }
-        }
+    for entry in report.entries.iter() {
+        println!(
+            "{} ({}): {}",
+            entry.service,
+            entry.title,
+            describe_outcome(&entry.outcome)
+        );
     }
+    println!();
+}
 
-    Ok(())
+fn print_settled() {
+    println!("Skipping code, the ledger shows nothing is left to redeem");
+    println!();
 }
 
-async fn redeem_form(client: &Client, form: &RewardForm) -> anyhow::Result<()> {
-    let redeem_response = client.redeem(form).await.context("Failed to redeem code")?;
-    println!("Redeemed code!");
+fn print_ledger_error(ledger: &Ledger) {
+    if let Some(error) = ledger.take_write_error() {
+        let error = anyhow::Error::from(error).context("Failed to update the ledger");
+        eprintln!("{error:?}");
+        eprintln!();
+    }
+}
 
-    if let Some(redeem_response) = redeem_response {
-        if let Some(text) = redeem_response.text {
-            println!("Response: {text}");
-        } else {
-            eprintln!("Unknown Redeem Response: {redeem_response:#?}");
-        }
+fn describe_outcome(outcome: &RedemptionOutcome) -> String {
+    match outcome {
+        RedemptionOutcome::Redeemed => "Redeemed code!".into(),
+        RedemptionOutcome::AlreadyRedeemed => "Already redeemed".into(),
+        RedemptionOutcome::Expired => "Expired".into(),
+        RedemptionOutcome::NonExistent => "Does not exist".into(),
+        RedemptionOutcome::NotAvailable => "Not available for this account".into(),
+        RedemptionOutcome::LaunchGame => "Launch a SHiFT-enabled game first".into(),
+        RedemptionOutcome::Failed => "Failed to redeem".into(),
+        RedemptionOutcome::Unknown(text) => format!("Unknown response: {text}"),
+        RedemptionOutcome::Error(error) => format!("Error: {error}"),
     }
+}
 
-    Ok(())
+/// Get the value following a command line flag, like the path in `--ledger <path>`
+fn flag_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    args.find(|arg| arg == flag)?;
+    args.next()
+}
+
+/// The log filter used by `--verbose` when `RUST_LOG` is not set
+const VERBOSE_FILTER: &str = "shift_client=debug,shift_orcz=debug";
+
+/// Set up logging to stderr.
+///
+/// Logs are shown if `RUST_LOG` is set or `--verbose` is passed.
+fn init_logging() {
+    let verbose = std::env::args()
+        .skip(1)
+        .any(|arg| arg == "--verbose" || arg == "-v");
+    let filter = match std::env::var(EnvFilter::DEFAULT_ENV) {
+        Ok(filter) => EnvFilter::new(filter),
+        Err(_) if verbose => EnvFilter::new(VERBOSE_FILTER),
+        Err(_) => return,
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
 }
 
 fn main() -> anyhow::Result<()> {
+    init_logging();
+
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()?;
@@ -170,15 +249,19 @@ fn main() -> anyhow::Result<()> {
 }
 
 async fn async_main() -> anyhow::Result<()> {
+    let ledger_path = flag_value("--ledger").unwrap_or_else(|| DEFAULT_LEDGER_PATH.into());
+    let ledger = Ledger::open(&ledger_path)
+        .with_context(|| format!("Failed to open the ledger at \"{ledger_path}\""))?;
+
     let client = login_client().await;
 
     println!("Would you like to use manual mode? (Y/N)");
     if input_yn() {
         println!("Using manual mode...");
-        manual_loop(&client).await;
+        manual_loop(&client, &ledger).await;
     } else {
         println!("Using auto mode...");
-        auto_loop(&client).await;
+        auto_loop(&client, &ledger).await;
     }
 
     Ok(())
@@ -0,0 +1,172 @@
+use crate::{
+    error::{ShiftError, ShiftResult},
+    types::{RedemptionReport, ShiftCode},
+    Client,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    sync::{Mutex, Semaphore},
+    task::JoinSet,
+    time::Instant,
+};
+
+/// The default number of accounts that redeem a code at the same time
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A pool of logged in [`Client`]s, one for each account, that redeems codes on every account.
+///
+/// A failure on one account does not stop the others.
+#[derive(Clone)]
+pub struct AccountPool {
+    accounts: Vec<PoolAccount>,
+    concurrency: usize,
+    min_code_interval: Option<Duration>,
+}
+
+impl AccountPool {
+    /// Make an empty [`AccountPool`]
+    pub fn new() -> Self {
+        Self {
+            accounts: Vec::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+            min_code_interval: None,
+        }
+    }
+
+    /// Set the maximum number of accounts that redeem a code at the same time.
+    ///
+    /// Defaults to [`DEFAULT_CONCURRENCY`]. A value of 0 is treated as 1.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the minimum interval between codes redeemed on the same account.
+    ///
+    /// This is on top of the [`ClientBuilder::min_redemption_interval`](crate::ClientBuilder::min_redemption_interval)
+    /// of each client, which limits every redemption request.
+    pub fn min_code_interval(mut self, interval: Duration) -> Self {
+        self.min_code_interval = Some(interval);
+        self
+    }
+
+    /// Add a logged in client
+    pub fn add(&mut self, client: Client) {
+        self.accounts.push(PoolAccount {
+            client,
+            last_code: Arc::default(),
+        });
+    }
+
+    /// Get the clients, in the order they were added
+    pub fn clients(&self) -> impl Iterator<Item = &Client> {
+        self.accounts.iter().map(|account| &account.client)
+    }
+
+    /// Get the number of accounts
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Check whether the pool has no accounts
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Redeem a code on every account, with [`Client::redeem_code`].
+    ///
+    /// Returns the result for each account, in the order they were added.
+    /// Dropping the returned future cancels the redemptions that have not finished.
+    pub async fn redeem_code(&self, code: &ShiftCode) -> Vec<AccountRedemption> {
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+
+        let mut tasks = JoinSet::new();
+        let mut indices = HashMap::with_capacity(self.accounts.len());
+        for (index, account) in self.accounts.iter().enumerate() {
+            let account = account.clone();
+            let semaphore = semaphore.clone();
+            let code = code.clone();
+            let min_code_interval = self.min_code_interval;
+
+            let redemption = async move {
+                account.wait_for_code_interval(min_code_interval).await;
+                let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+                account.client.redeem_code(&code).await
+            };
+            #[cfg(feature = "tracing")]
+            let redemption =
+                tracing::Instrument::instrument(redemption, tracing::info_span!("account", index));
+            let task = tasks.spawn(redemption);
+            indices.insert(task.id(), index);
+        }
+
+        let mut results: Vec<Option<ShiftResult<RedemptionReport>>> =
+            (0..self.accounts.len()).map(|_| None).collect();
+        while let Some(task) = tasks.join_next_with_id().await {
+            let (id, result) = match task {
+                Ok((id, result)) => (id, result),
+                Err(error) => (error.id(), Err(ShiftError::from(error))),
+            };
+            results[indices[&id]] = Some(result);
+        }
+
+        self.accounts
+            .iter()
+            .zip(results)
+            .map(|(account, result)| AccountRedemption {
+                email: account.client.email(),
+                result: result.expect("missing redemption result"),
+            })
+            .collect()
+    }
+}
+
+impl Default for AccountPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extend<Client> for AccountPool {
+    fn extend<I: IntoIterator<Item = Client>>(&mut self, clients: I) {
+        for client in clients {
+            self.add(client);
+        }
+    }
+}
+
+impl FromIterator<Client> for AccountPool {
+    fn from_iter<I: IntoIterator<Item = Client>>(clients: I) -> Self {
+        let mut pool = Self::new();
+        pool.extend(clients);
+        pool
+    }
+}
+
+/// The result of redeeming a code on one account of an [`AccountPool`]
+#[derive(Debug)]
+pub struct AccountRedemption {
+    /// The email of the account
+    pub email: String,
+
+    /// The report, or the error that stopped the redemption on this account
+    pub result: ShiftResult<RedemptionReport>,
+}
+
+/// An account of an [`AccountPool`]
+#[derive(Clone)]
+struct PoolAccount {
+    client: Client,
+    last_code: Arc<Mutex<Option<Instant>>>,
+}
+
+impl PoolAccount {
+    /// Wait until the minimum interval since the last code redeemed on this account has passed.
+    async fn wait_for_code_interval(&self, interval: Option<Duration>) {
+        let mut last_code = self.last_code.lock().await;
+        if let (Some(interval), Some(last)) = (interval, *last_code) {
+            tokio::time::sleep_until(last + interval).await;
+        }
+        *last_code = Some(Instant::now());
+    }
+}
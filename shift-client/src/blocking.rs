@@ -0,0 +1,163 @@
+//! A blocking [`Client`], for code that does not use async.
+//!
+//! Each client drives the async [`crate::Client`] on its own single-threaded Tokio runtime.
+//! It must not be used or dropped inside an async runtime.
+
+use crate::{
+    error::ShiftResult,
+    types::{
+        rewards::{CodeRedemptionJson, RewardForm, RewardsPage},
+        AccountPage, AccountPreferencesPage, AssociationsPage, ProfileUpdate, RedemptionReport,
+        ShiftCode,
+    },
+    Session,
+};
+use std::{future::Future, sync::Arc};
+
+/// A blocking shift client.
+///
+/// Build one with [`ClientBuilder`](crate::ClientBuilder) and convert it with [`From`] to customize it.
+#[derive(Clone)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl Client {
+    /// Make a new shift client, not logged in
+    pub fn new(email: String, password: String) -> Self {
+        crate::Client::new(email, password).into()
+    }
+
+    /// Make a shift client from an exported [`Session`].
+    ///
+    /// See [`crate::Client::from_session`].
+    pub fn from_session(session: Session) -> Self {
+        crate::Client::from_session(session).into()
+    }
+
+    /// Get the async client this client drives
+    pub fn as_async(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// Run a future on the runtime of this client
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Export the current session.
+    ///
+    /// See [`crate::Client::session`].
+    pub fn session(&self) -> ShiftResult<Session> {
+        self.inner.session()
+    }
+
+    /// Logs in and allows making other requests
+    pub fn login(&self) -> ShiftResult<AccountPage> {
+        self.block_on(self.inner.login())
+    }
+
+    /// Get the [`AccountPage`], checking that the current session is still valid.
+    ///
+    /// See [`crate::Client::check_session`].
+    pub fn check_session(&self) -> ShiftResult<AccountPage> {
+        self.block_on(self.inner.check_session())
+    }
+
+    /// Log out, ending the session.
+    pub fn logout(&self) -> ShiftResult<()> {
+        self.block_on(self.inner.logout())
+    }
+
+    /// Update the profile, returning the updated [`AccountPage`].
+    ///
+    /// See [`crate::Client::update_profile`].
+    pub fn update_profile(&self, update: ProfileUpdate) -> ShiftResult<AccountPage> {
+        self.block_on(self.inner.update_profile(update))
+    }
+
+    /// Change the password, returning the [`AccountPage`].
+    ///
+    /// See [`crate::Client::change_password`].
+    pub fn change_password(
+        &self,
+        current_password: &str,
+        new_password: &str,
+    ) -> ShiftResult<AccountPage> {
+        self.block_on(self.inner.change_password(current_password, new_password))
+    }
+
+    /// Get the [`AccountPreferencesPage`]
+    pub fn get_preferences(&self) -> ShiftResult<AccountPreferencesPage> {
+        self.block_on(self.inner.get_preferences())
+    }
+
+    /// Submit the preferences of the given page, returning the updated [`AccountPreferencesPage`].
+    pub fn set_preferences(
+        &self,
+        page: &AccountPreferencesPage,
+    ) -> ShiftResult<AccountPreferencesPage> {
+        self.block_on(self.inner.set_preferences(page))
+    }
+
+    /// Get the [`AssociationsPage`], listing the linked gaming platforms.
+    ///
+    /// See [`crate::Client::get_associations`].
+    pub fn get_associations(&self) -> ShiftResult<AssociationsPage> {
+        self.block_on(self.inner.get_associations())
+    }
+
+    /// Get the [`RewardsPage`]
+    pub fn get_rewards_page(&self) -> ShiftResult<RewardsPage> {
+        self.block_on(self.inner.get_rewards_page())
+    }
+
+    /// Get the [`RewardForm`]s for a code.
+    ///
+    /// See [`crate::Client::get_reward_forms`].
+    pub fn get_reward_forms(
+        &self,
+        rewards_page: &RewardsPage,
+        code: &ShiftCode,
+    ) -> ShiftResult<Vec<RewardForm>> {
+        self.block_on(self.inner.get_reward_forms(rewards_page, code))
+    }
+
+    /// Redeem a code
+    pub fn redeem(&self, form: &RewardForm) -> ShiftResult<Option<CodeRedemptionJson>> {
+        self.block_on(self.inner.redeem(form))
+    }
+
+    /// Redeem a code for every offered service and title.
+    ///
+    /// See [`crate::Client::redeem_code`].
+    pub fn redeem_code(&self, code: &ShiftCode) -> ShiftResult<RedemptionReport> {
+        self.block_on(self.inner.redeem_code(code))
+    }
+}
+
+impl From<crate::Client> for Client {
+    fn from(inner: crate::Client) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build tokio runtime");
+
+        Self {
+            inner,
+            runtime: Arc::new(runtime),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn session() {
+        let client = Client::new("user@example.com".into(), "password".into());
+        assert_eq!(client.session().unwrap().email, "user@example.com");
+    }
+}
@@ -1,102 +1,666 @@
+mod builder;
+mod poll_policy;
+mod retry_policy;
+
+#[cfg(feature = "ledger")]
+use crate::ledger::Ledger;
 use crate::{
-    error::{ShiftError, ShiftResult},
+    error::{PageParseError, ShiftError, ShiftResult},
     types::{
         rewards::{AlertNotice, CodeRedemptionJson, CodeRedemptionPage, RewardForm, RewardsPage},
-        AccountPage, HomePage,
+        AccountPage, AccountPreferencesPage, AssociationsPage, HomePage, Interstitial,
+        PasswordChangePage, ProfileUpdate, RedemptionEntry, RedemptionOutcome, RedemptionReport,
+        Service, ShiftCode,
     },
+    util::{dump_body, html_excerpt},
+    Session,
 };
+use reqwest::{ResponseBuilderExt, StatusCode, Url};
+use reqwest_cookie_store::CookieStoreMutex;
 use scraper::Html;
+use shift_transport::Transport;
 use std::{
+    path::Path,
     sync::{Arc, RwLock},
     time::Duration,
 };
 
-const HOME_URL: &str = "https://shift.gearboxsoftware.com/home";
-const SESSIONS_URL: &str = "https://shift.gearboxsoftware.com/sessions";
-const CODE_REDEMPTIONS_URL: &str = "https://shift.gearboxsoftware.com/code_redemptions";
-const REWARDS_URL: &str = "https://shift.gearboxsoftware.com/rewards";
-const ENTITLEMENT_OFFER_CODES_URL: &str =
-    "https://shift.gearboxsoftware.com/entitlement_offer_codes";
+use self::retry_policy::{is_retryable, parse_retry_after};
+
+/// The exception named in the body of a `422` response when the server rejected a csrf token
+const INVALID_AUTHENTICITY_TOKEN: &[u8] = b"InvalidAuthenticityToken";
+
+pub use self::{
+    builder::{ClientBuilder, DEFAULT_BASE_URL},
+    poll_policy::PollPolicy,
+    retry_policy::{ExponentialBackoff, NoRetry, RetryPolicy},
+};
 
 #[derive(Clone)]
 pub struct Client {
     client: reqwest::Client,
+    cookie_store: Arc<CookieStoreMutex>,
+    owns_cookie_store: bool,
     client_data: Arc<RwLock<ClientData>>,
+    urls: Arc<Urls>,
+    poll_policy: PollPolicy,
+    retry_policy: Arc<dyn RetryPolicy>,
+    min_redemption_interval: Option<Duration>,
+    services: Option<Arc<[Service]>>,
+    auto_relogin: bool,
+    last_redemption: Arc<tokio::sync::Mutex<Option<tokio::time::Instant>>>,
+    parse_error_dump_dir: Option<Arc<Path>>,
+    transport: Arc<dyn Transport>,
 }
 
 impl Client {
     /// Make a new shift client, not logged in
     pub fn new(email: String, password: String) -> Self {
-        Self {
-            client: reqwest::Client::builder()
-                .cookie_store(true)
-                .build()
-                .expect("failed to build reqwest client"),
+        ClientBuilder::new(email, password)
+            .build()
+            .expect("failed to build client")
+    }
+
+    /// Make a [`ClientBuilder`] for a new shift client, not logged in
+    pub fn builder(email: String, password: String) -> ClientBuilder {
+        ClientBuilder::new(email, password)
+    }
+
+    /// Make a shift client from an exported [`Session`].
+    ///
+    /// The session is not checked; use [`Client::check_session`] to make sure it is still valid.
+    /// Since a session does not contain the password, [`Client::login`] cannot be used with this client.
+    pub fn from_session(session: Session) -> Self {
+        ClientBuilder::from_session(session)
+            .build()
+            .expect("failed to build client")
+    }
+
+    /// Get the email of the account
+    pub fn email(&self) -> String {
+        self.client_data
+            .read()
+            .expect("client data poisoned")
+            .email
+            .clone()
+    }
+
+    /// Export the current session, so that it can be restored with [`Client::from_session`].
+    ///
+    /// # Errors
+    /// Returns [`ShiftError::SessionWithReqwestClient`] if the client was built with
+    /// [`ClientBuilder::reqwest_client`], since its cookies are not visible to this client.
+    pub fn session(&self) -> ShiftResult<Session> {
+        if !self.owns_cookie_store {
+            return Err(ShiftError::SessionWithReqwestClient);
+        }
+
+        let email = self.email();
+        let cookie_store = self.cookie_store.lock().expect("cookie store poisoned");
+        Ok(Session::from_cookie_store(email, &cookie_store))
+    }
+
+    /// Send a request, retrying it according to the [`RetryPolicy`].
+    ///
+    /// If the request is still rate limited after the last retry, [`ShiftError::RateLimited`] is returned.
+    /// Other error statuses are returned as a response.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(method, url))
+    )]
+    async fn send(&self, req: reqwest::RequestBuilder) -> ShiftResult<reqwest::Response> {
+        let req = req.build()?;
+        let mut retries = 0;
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            span.record("method", tracing::field::display(req.method()));
+            span.record("url", tracing::field::display(req.url()));
+        }
+
+        loop {
+            #[cfg(feature = "tracing")]
+            let started = std::time::Instant::now();
+            let mut res = self
+                .transport
+                .execute(
+                    &self.client,
+                    req.try_clone().expect("request body is not cloneable"),
+                )
+                .await?;
+            res.extensions_mut().insert(RequestUrl(req.url().clone()));
+
+            let status = res.status();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                status = status.as_u16(),
+                redirected_to = (res.url() != req.url()).then(|| res.url().as_str()),
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "got response"
+            );
+            if !is_retryable(req.method(), status) {
+                return Ok(res);
+            }
+
+            let retry_after = parse_retry_after(res.headers());
+            let delay = self.retry_policy.retry_delay(retries, status, retry_after);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                status = status.as_u16(),
+                retries,
+                retry_after_ms = retry_after.map(|delay| delay.as_millis() as u64),
+                delay_ms = delay.map(|delay| delay.as_millis() as u64),
+                retrying = delay.is_some(),
+                "request was rate limited or hit a server error"
+            );
+            match delay {
+                Some(delay) => {
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                }
+                None if status == StatusCode::TOO_MANY_REQUESTS => {
+                    return Err(ShiftError::RateLimited { retry_after });
+                }
+                None => return Ok(res),
+            }
+        }
+    }
+
+    /// Convert a response to html, then feed it to the given transform function.
+    ///
+    /// Parse errors are wrapped in a [`PageParseError`] describing the response.
+    async fn res_to_html_transform<F, T>(&self, res: reqwest::Response, f: F) -> ShiftResult<T>
+    where
+        F: Fn(Html) -> ShiftResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let info = ResponseInfo::new(&res);
+        let status_error = res.error_for_status_ref().err();
+        let text = res.text().await?;
+        let dump_dir = self.parse_error_dump_dir.clone();
+        let ret = tokio::task::spawn_blocking(move || {
+            let interstitial = |info: &ResponseInfo| {
+                let html = Html::parse_document(text.as_str());
+                Interstitial::detect(&html, &info.url, info.status.as_u16())
+                    .map(|interstitial| ShiftError::from_interstitial(interstitial, &info.url))
+            };
+            if let Some(error) = status_error {
+                return Err(interstitial(&info).unwrap_or_else(|| error.into()));
+            }
+            // Only classify the page once it fails to parse, so a normal page that mentions maintenance is still used.
+            f(Html::parse_document(text.as_str())).map_err(|error| match interstitial(&info) {
+                Some(error) => error,
+                None => info.into_parse_error(error, &text, dump_dir.as_deref()),
+            })
+        })
+        .await?;
+
+        #[cfg(feature = "tracing")]
+        match &ret {
+            Ok(_) => tracing::debug!(page = std::any::type_name::<T>(), "parsed page"),
+            Err(error) => {
+                tracing::warn!(page = std::any::type_name::<T>(), %error, "failed to parse page")
+            }
+        }
+
+        ret
+    }
+
+    /// Send a request that needs a logged in session.
+    ///
+    /// If the session has expired or the csrf token was rejected,
+    /// this logs in again, refreshes the csrf token, and replays the request once.
+    /// `make_request` is given the current csrf token,
+    /// and the given csrf token is updated if it was refreshed.
+    /// If logging in again is disabled or not possible, [`ShiftError::SessionExpired`] is returned.
+    async fn send_authenticated<F>(
+        &self,
+        mut csrf_token: Option<&mut String>,
+        make_request: F,
+    ) -> ShiftResult<reqwest::Response>
+    where
+        F: Fn(Option<&str>) -> reqwest::RequestBuilder,
+    {
+        let res = self
+            .send(make_request(csrf_token.as_deref().map(String::as_str)))
+            .await?;
+        if let Some(res) = self.unless_session_expired(res).await? {
+            return Ok(res);
+        }
+
+        let can_relogin = self.auto_relogin && {
+            let lock = self.client_data.read().expect("client data poisoned");
+            !lock.logged_out && lock.password.is_some()
+        };
+        if !can_relogin {
+            return Err(ShiftError::SessionExpired);
+        }
+
+        let fresh_csrf_token = self.relogin().await?;
+        if let Some(csrf_token) = csrf_token.as_mut() {
+            **csrf_token = fresh_csrf_token;
+        }
+
+        let res = self
+            .send(make_request(csrf_token.as_deref().map(String::as_str)))
+            .await?;
+        self.unless_session_expired(res)
+            .await?
+            .ok_or(ShiftError::SessionExpired)
+    }
+
+    /// Get the response back, or `None` if it shows that the session expired or the csrf token was rejected.
+    ///
+    /// The session expired if the response is a `401 Unauthorized` or a redirect to the sign in page.
+    /// The csrf token was rejected if the response is a `422 Unprocessable Entity` for an `InvalidAuthenticityToken`.
+    /// Any other `422` is given back, rebuilt from its body.
+    async fn unless_session_expired(
+        &self,
+        res: reqwest::Response,
+    ) -> ShiftResult<Option<reqwest::Response>> {
+        if res.status() == StatusCode::UNAUTHORIZED
+            || res.url().as_str().starts_with(self.urls.home.as_str())
+        {
+            return Ok(None);
+        }
+        if res.status() != StatusCode::UNPROCESSABLE_ENTITY {
+            return Ok(Some(res));
+        }
+
+        let status = res.status();
+        let url = res.url().clone();
+        let headers = res.headers().clone();
+        let body = res.bytes().await?;
+        if body
+            .windows(INVALID_AUTHENTICITY_TOKEN.len())
+            .any(|window| window == INVALID_AUTHENTICITY_TOKEN)
+        {
+            return Ok(None);
+        }
+
+        let mut builder = http::Response::builder().status(status).url(url);
+        if let Some(builder_headers) = builder.headers_mut() {
+            *builder_headers = headers;
+        }
+        let res = builder
+            .body(body)
+            .expect("response parts from a valid response");
+        Ok(Some(res.into()))
+    }
+
+    /// Log in again, returning a fresh csrf token.
+    async fn relogin(&self) -> ShiftResult<String> {
+        #[cfg(feature = "tracing")]
+        tracing::info!("session expired, logging in again");
+
+        self.login().await?;
+
+        let res = self
+            .send(self.client.get(self.urls.rewards.clone()))
+            .await?;
+        let page = self
+            .res_to_html_transform(res, |html| Ok(RewardsPage::from_html(&html)?))
+            .await?;
+        Ok(page.csrf_token)
+    }
 
-            client_data: Arc::new(RwLock::new(ClientData { email, password })),
+    /// Wait until the minimum interval since the last redemption request has passed.
+    async fn wait_for_redemption_interval(&self) {
+        let mut last_redemption = self.last_redemption.lock().await;
+        if let (Some(interval), Some(last)) = (self.min_redemption_interval, *last_redemption) {
+            tokio::time::sleep_until(last + interval).await;
         }
+        *last_redemption = Some(tokio::time::Instant::now());
     }
 
     /// Get the home page. Does not need authentication.
     async fn get_home_page(&self) -> ShiftResult<HomePage> {
-        let res = self.client.get(HOME_URL).send().await?;
-        let home_page = res_to_html_transform(res, |html| Ok(HomePage::from_html(&html)?)).await?;
+        let res = self.send(self.client.get(self.urls.home.clone())).await?;
+        let home_page = self
+            .res_to_html_transform(res, |html| Ok(HomePage::from_html(&html)?))
+            .await?;
         Ok(home_page)
     }
 
     /// Logs in and allows making other requests
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip_all))]
     pub async fn login(&self) -> ShiftResult<AccountPage> {
         let home_page = self.get_home_page().await?;
 
         let req = {
             let lock = self.client_data.read().expect("client data poisoned");
-            self.client.post(SESSIONS_URL).form(&[
+            let password = lock
+                .password
+                .as_deref()
+                .ok_or(ShiftError::MissingPassword)?;
+            self.client.post(self.urls.sessions.clone()).form(&[
                 ("utf8", "✓"),
                 ("authenticity_token", &home_page.csrf_token),
                 ("user[email]", &lock.email),
-                ("user[password]", &lock.password),
+                ("user[password]", password),
                 ("commit", "SIGN IN"),
             ])
         };
-        let res = req.send().await?;
+        let res = self.send(req).await?;
 
-        match res.url().as_str() {
-            "https://shift.gearboxsoftware.com/home?redirect_to=false" => {
-                return Err(ShiftError::IncorrectEmailOrPassword);
-            }
-            "https://shift.gearboxsoftware.com/account" => {}
-            url => {
-                return Err(ShiftError::InvalidRedirect(url.into()));
-            }
+        let url = res.url();
+        if *url == self.urls.home_sign_in_failed {
+            return Err(ShiftError::IncorrectEmailOrPassword);
+        }
+        if *url != self.urls.account {
+            let fallback = ShiftError::InvalidRedirect(url.as_str().into());
+            return Err(unexpected_page_error(res, fallback).await);
         }
 
-        let account_page =
-            res_to_html_transform(res, |html| Ok(AccountPage::from_html(&html)?)).await?;
+        let account_page = self
+            .res_to_html_transform(res, |html| Ok(AccountPage::from_html(&html)?))
+            .await?;
+        self.add_secrets([
+            account_page.email.as_str(),
+            account_page.display_name.as_str(),
+            account_page.first_name.as_str(),
+        ])?;
+        self.client_data
+            .write()
+            .expect("client data poisoned")
+            .logged_out = false;
         Ok(account_page)
     }
 
+    /// Add personal details to the transport as secrets, so they are never saved
+    fn add_secrets<'a>(&self, secrets: impl IntoIterator<Item = &'a str>) -> ShiftResult<()> {
+        for secret in secrets {
+            self.transport.add_secret(secret)?;
+        }
+        Ok(())
+    }
+
+    /// Get the [`AccountPage`].
+    ///
+    /// This is also a cheap way to check that the current session is still valid.
+    /// If the session has expired, [`ShiftError::SessionExpired`] is returned.
+    pub async fn check_session(&self) -> ShiftResult<AccountPage> {
+        let res = self
+            .send(self.client.get(self.urls.account.clone()))
+            .await?;
+
+        if !res.url().as_str().starts_with(self.urls.account.as_str()) {
+            return Err(unexpected_page_error(res, ShiftError::SessionExpired).await);
+        }
+
+        let account_page = self
+            .res_to_html_transform(res, |html| Ok(AccountPage::from_html(&html)?))
+            .await?;
+
+        let email_matches = {
+            let lock = self.client_data.read().expect("client data poisoned");
+            account_page.email.eq_ignore_ascii_case(&lock.email)
+        };
+        if !email_matches {
+            return Err(ShiftError::SessionExpired);
+        }
+
+        Ok(account_page)
+    }
+
+    /// Log out, ending the session.
+    ///
+    /// This also stops the client from logging in again when a later request finds the session expired;
+    /// those requests return [`ShiftError::SessionExpired`] until [`Client::login`] is called.
+    pub async fn logout(&self) -> ShiftResult<()> {
+        self.client_data
+            .write()
+            .expect("client data poisoned")
+            .logged_out = true;
+
+        let res = self
+            .send(self.client.get(self.urls.logout.clone()))
+            .await?
+            .error_for_status()?;
+
+        if res.url().as_str().starts_with(self.urls.account.as_str()) {
+            return Err(ShiftError::InvalidRedirect(res.url().as_str().into()));
+        }
+
+        Ok(())
+    }
+
+    /// Get the [`AccountPage`], logging in again if needed
+    async fn get_account_page(&self) -> ShiftResult<AccountPage> {
+        let res = self
+            .send_authenticated(None, |_| self.client.get(self.urls.account.clone()))
+            .await?;
+        let page = self
+            .res_to_html_transform(res, |html| Ok(AccountPage::from_html(&html)?))
+            .await?;
+        Ok(page)
+    }
+
+    /// Update the profile, returning the updated [`AccountPage`].
+    ///
+    /// After submitting the form, the account page is fetched again.
+    /// If it does not show the new display name and first name, [`ShiftError::ProfileUpdateFailed`] is returned.
+    /// A new email may need confirming before the account page shows it, so it is not checked.
+    ///
+    /// The live profile form is submitted by javascript (`data-remote="true"`),
+    /// so what the site responds with to this plain form post has not been checked yet.
+    /// The response is ignored unless it is an error status, and success is only judged from the refetched page.
+    pub async fn update_profile(&self, update: ProfileUpdate) -> ShiftResult<AccountPage> {
+        let account_page = self.get_account_page().await?;
+        let action = account_page
+            .profile_form_action
+            .as_deref()
+            .ok_or(ShiftError::MissingProfileForm)?;
+        let url = self
+            .urls
+            .base
+            .join(action)
+            .map_err(|_| ShiftError::MissingProfileForm)?;
+
+        let email = update.email.as_deref().unwrap_or(&account_page.email);
+        let display_name = update
+            .display_name
+            .as_deref()
+            .unwrap_or(&account_page.display_name);
+        let first_name = update
+            .first_name
+            .as_deref()
+            .unwrap_or(&account_page.first_name);
+
+        self.add_secrets([email, display_name, first_name])?;
+
+        let mut csrf_token = account_page.csrf_token.clone();
+        self.send_authenticated(Some(&mut csrf_token), |csrf_token| {
+            self.client
+                .post(url.clone())
+                .header("X-CSRF-Token", csrf_token.unwrap_or_default())
+                .form(&[
+                    ("utf8", "✓"),
+                    ("_method", "patch"),
+                    ("authenticity_token", csrf_token.unwrap_or_default()),
+                    ("user[email]", email),
+                    ("user[display_name]", display_name),
+                    ("user[first_name]", first_name),
+                    ("commit", "Update profile"),
+                ])
+        })
+        .await?
+        .error_for_status()?;
+
+        let updated_page = self.get_account_page().await?;
+        if updated_page.display_name != display_name || updated_page.first_name != first_name {
+            return Err(ShiftError::ProfileUpdateFailed);
+        }
+
+        Ok(updated_page)
+    }
+
+    /// Change the password, returning the [`AccountPage`].
+    ///
+    /// The new password is used from then on when logging in again.
+    /// The change only counts as successful if the site redirects to the account page;
+    /// anything else, like the form shown again with errors, is [`ShiftError::PasswordChangeFailed`]
+    /// and keeps the old password.
+    ///
+    /// The page parser and this success check have not been checked against the live site yet;
+    /// see [`PasswordChangePage`].
+    pub async fn change_password(
+        &self,
+        current_password: &str,
+        new_password: &str,
+    ) -> ShiftResult<AccountPage> {
+        let res = self
+            .send_authenticated(None, |_| self.client.get(self.urls.password_change.clone()))
+            .await?;
+        let page = self
+            .res_to_html_transform(res, |html| Ok(PasswordChangePage::from_html(&html)?))
+            .await?;
+        let url = self
+            .urls
+            .base
+            .join(&page.action)
+            .map_err(|_| ShiftError::InvalidRedirect(page.action.clone()))?;
+
+        let mut csrf_token = page.csrf_token.clone();
+        let res = self
+            .send_authenticated(Some(&mut csrf_token), |csrf_token| {
+                let csrf_token = csrf_token.unwrap_or_default();
+                let mut fields: Vec<(&str, &str)> = page
+                    .hidden_fields
+                    .iter()
+                    .map(|(name, value)| match name.as_str() {
+                        "authenticity_token" => (name.as_str(), csrf_token),
+                        _ => (name.as_str(), value.as_str()),
+                    })
+                    .collect();
+                fields.push((&page.current_password_name, current_password));
+                fields.push((&page.new_password_name, new_password));
+                if let Some(name) = page.password_confirmation_name.as_deref() {
+                    fields.push((name, new_password));
+                }
+
+                self.client.post(url.clone()).form(&fields)
+            })
+            .await?
+            .error_for_status()?;
+
+        if res.url().path() != self.urls.account.path() {
+            return Err(ShiftError::PasswordChangeFailed);
+        }
+
+        self.client_data
+            .write()
+            .expect("client data poisoned")
+            .password = Some(new_password.to_string());
+
+        self.get_account_page().await
+    }
+
+    /// Get the [`AccountPreferencesPage`], listing the newsletter and notification toggles.
+    ///
+    /// The page parser has not been checked against the live site yet; see [`AccountPreferencesPage`].
+    pub async fn get_preferences(&self) -> ShiftResult<AccountPreferencesPage> {
+        let res = self
+            .send_authenticated(None, |_| self.client.get(self.urls.preferences.clone()))
+            .await?;
+        let page = self
+            .res_to_html_transform(res, |html| Ok(AccountPreferencesPage::from_html(&html)?))
+            .await?;
+        Ok(page)
+    }
+
+    /// Submit the preferences of the given page, returning the updated [`AccountPreferencesPage`].
+    ///
+    /// Get the page with [`Client::get_preferences`] and change its preferences before submitting it.
+    /// The page is fetched again after submitting, and if any preference did not keep the submitted value,
+    /// [`ShiftError::PreferencesUpdateFailed`] is returned.
+    pub async fn set_preferences(
+        &self,
+        page: &AccountPreferencesPage,
+    ) -> ShiftResult<AccountPreferencesPage> {
+        let url = self
+            .urls
+            .base
+            .join(&page.action)
+            .map_err(|_| ShiftError::InvalidRedirect(page.action.clone()))?;
+
+        let mut csrf_token = page.csrf_token.clone();
+        self.send_authenticated(Some(&mut csrf_token), |csrf_token| {
+            self.client
+                .post(url.clone())
+                .form(&page.form_fields(csrf_token.unwrap_or_default()))
+        })
+        .await?
+        .error_for_status()?;
+
+        let updated_page = self.get_preferences().await?;
+        let names: Vec<String> = page
+            .preferences
+            .iter()
+            .filter(|preference| {
+                updated_page
+                    .get(&preference.name)
+                    .is_none_or(|updated| updated.enabled != preference.enabled)
+            })
+            .map(|preference| preference.name.clone())
+            .collect();
+        if !names.is_empty() {
+            return Err(ShiftError::PreferencesUpdateFailed { names });
+        }
+
+        Ok(updated_page)
+    }
+
+    /// Get the [`AssociationsPage`], listing the linked gaming platforms.
+    ///
+    /// The page parser has not been checked against the live site yet; see [`AssociationsPage`].
+    pub async fn get_associations(&self) -> ShiftResult<AssociationsPage> {
+        let res = self
+            .send_authenticated(None, |_| self.client.get(self.urls.associations.clone()))
+            .await?;
+        let page = self
+            .res_to_html_transform(res, |html| Ok(AssociationsPage::from_html(&html)?))
+            .await?;
+        Ok(page)
+    }
+
     /// Get the [`RewardsPage`]
     pub async fn get_rewards_page(&self) -> ShiftResult<RewardsPage> {
-        let res = self.client.get(REWARDS_URL).send().await?;
-        let page = res_to_html_transform(res, |html| Ok(RewardsPage::from_html(&html)?)).await?;
+        let res = self
+            .send_authenticated(None, |_| self.client.get(self.urls.rewards.clone()))
+            .await?;
+        let page = self
+            .res_to_html_transform(res, |html| Ok(RewardsPage::from_html(&html)?))
+            .await?;
         Ok(page)
     }
 
+    /// Get the [`RewardForm`]s for a code, one for each service and title it can be redeemed for.
+    ///
+    /// If the client was built with [`ClientBuilder::services`], only forms for those services are returned.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(%code))
+    )]
     pub async fn get_reward_forms(
         &self,
         rewards_page: &RewardsPage,
-        code: &str,
+        code: &ShiftCode,
     ) -> ShiftResult<Vec<RewardForm>> {
+        let mut csrf_token = rewards_page.csrf_token.clone();
         let res = self
-            .client
-            .get(ENTITLEMENT_OFFER_CODES_URL)
-            .query(&[("code", code)])
-            .header("X-CSRF-Token", &rewards_page.csrf_token)
-            .header("X-Requested-With", "XMLHttpRequest")
-            .send()
+            .send_authenticated(Some(&mut csrf_token), |csrf_token| {
+                self.client
+                    .get(self.urls.entitlement_offer_codes.clone())
+                    .query(&[("code", code.as_str())])
+                    .header("X-CSRF-Token", csrf_token.unwrap_or_default())
+                    .header("X-Requested-With", "XMLHttpRequest")
+            })
             .await?
             .error_for_status()?;
 
+        let info = ResponseInfo::new(&res);
         let body = res.text().await?;
 
         match body.as_str().trim() {
@@ -108,29 +672,53 @@ impl Client {
             _ => {}
         }
 
-        let forms = tokio::task::spawn_blocking(move || {
+        let dump_dir = self.parse_error_dump_dir.clone();
+        let mut forms = tokio::task::spawn_blocking(move || {
             let html = Html::parse_document(body.as_str());
             RewardForm::from_html(&html)
+                .map_err(|error| info.into_parse_error(error.into(), &body, dump_dir.as_deref()))
         })
         .await??;
 
+        if let Some(services) = self.services.as_deref() {
+            forms.retain(|form| services.contains(&form.service()));
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(forms = forms.len(), "got reward forms");
+
         Ok(forms)
     }
 
     /// Redeem a code
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "info",
+            skip_all,
+            fields(service = %form.service(), title = %form.title())
+        )
+    )]
     pub async fn redeem(&self, form: &RewardForm) -> ShiftResult<Option<CodeRedemptionJson>> {
+        self.wait_for_redemption_interval().await;
+        let mut csrf_token = form.authenticity_token().to_string();
         let res = self
-            .client
-            .post(CODE_REDEMPTIONS_URL)
-            .form(&form)
-            .send()
+            .send_authenticated(Some(&mut csrf_token), |csrf_token| {
+                let form = form.to_post(csrf_token.unwrap_or_default());
+                self.client
+                    .post(self.urls.code_redemptions.clone())
+                    .form(&form)
+            })
             .await?;
 
         let url = res.url().as_str();
-        if url.starts_with(REWARDS_URL) {
-            let page =
-                res_to_html_transform(res, |html| Ok(RewardsPage::from_html(&html)?)).await?;
+        if url.starts_with(self.urls.rewards.as_str()) {
+            let page = self
+                .res_to_html_transform(res, |html| Ok(RewardsPage::from_html(&html)?))
+                .await?;
             let alert_notice = page.alert_notice.ok_or(ShiftError::MissingAlertNotice)?;
+            #[cfg(feature = "tracing")]
+            tracing::info!(?alert_notice, "redemption ended on the rewards page");
             match alert_notice {
                 AlertNotice::ShiftCodeAlreadyRedeemed => {
                     return Err(ShiftError::ShiftCodeAlreadyRedeemed);
@@ -144,52 +732,334 @@ impl Client {
                 AlertNotice::ShiftCodeRedeemFail => {
                     return Err(ShiftError::ShiftCodeRedeemFail);
                 }
+                AlertNotice::Other { text, kind } => {
+                    return Err(ShiftError::UnrecognizedAlertNotice { text, kind });
+                }
             }
         }
 
-        if !url.starts_with(CODE_REDEMPTIONS_URL) {
-            return Err(ShiftError::InvalidRedirect(url.into()));
+        if !url.starts_with(self.urls.code_redemptions.as_str()) {
+            let fallback = ShiftError::InvalidRedirect(url.into());
+            return Err(unexpected_page_error(res, fallback).await);
         }
 
-        let page =
-            res_to_html_transform(res, |html| Ok(CodeRedemptionPage::from_html(&html)?)).await?;
+        let base_url = self.urls.base.clone();
+        let page = self
+            .res_to_html_transform(res, move |html| {
+                Ok(CodeRedemptionPage::from_html(&html, &base_url)?)
+            })
+            .await?;
+
+        let res = self.poll_redemption_status(&page).await?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(status = ?res.status(), json = ?res, "redemption finished");
+
+        Ok(Some(res))
+    }
+
+    /// Poll the redemption status until it is no longer in progress, following the [`PollPolicy`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    async fn poll_redemption_status(
+        &self,
+        page: &CodeRedemptionPage,
+    ) -> ShiftResult<CodeRedemptionJson> {
+        let policy = self.poll_policy;
+        let deadline = policy
+            .deadline
+            .map(|deadline| tokio::time::Instant::now() + deadline);
+        let mut delay = policy.initial_delay;
+        let mut attempts = 0;
+        let mut csrf_token = page.csrf_token.clone();
 
-        let res = loop {
+        loop {
             let json: CodeRedemptionJson = self
-                .client
-                .get(&page.check_redemption_status_url)
-                .header("X-CSRF-Token", &page.csrf_token)
-                .header("X-Requested-With", "XMLHttpRequest")
-                .send()
+                .send_authenticated(Some(&mut csrf_token), |csrf_token| {
+                    self.client
+                        .get(&page.check_redemption_status_url)
+                        .header("X-CSRF-Token", csrf_token.unwrap_or_default())
+                        .header("X-Requested-With", "XMLHttpRequest")
+                })
                 .await?
                 .error_for_status()?
                 .json()
                 .await?;
+            attempts += 1;
 
-            tokio::time::sleep(Duration::from_secs(2)).await;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(attempts, status = ?json.status(), "polled redemption status");
 
             if !json.in_progress() {
-                break json;
+                return Ok(json);
+            }
+
+            let past_deadline =
+                deadline.is_some_and(|deadline| tokio::time::Instant::now() + delay > deadline);
+            if attempts >= policy.max_attempts || past_deadline {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(attempts, past_deadline, "gave up polling redemption status");
+                return Err(ShiftError::RedemptionStatusTimeout {
+                    last: Box::new(json),
+                });
             }
+
+            tokio::time::sleep(delay).await;
+            delay = policy.next_delay(delay);
+        }
+    }
+
+    /// Redeem a code for every offered service and title.
+    ///
+    /// This gets the [`RewardsPage`], gets the [`RewardForm`]s for the code, and redeems each one.
+    /// Rejections reported by the server are collected into the returned [`RedemptionReport`].
+    /// An error while redeeming one form is recorded as [`RedemptionOutcome::Error`] for it,
+    /// and the other forms are still redeemed.
+    /// Errors before any form is reached, like a failed code check, are returned.
+    pub async fn redeem_code(&self, code: &ShiftCode) -> ShiftResult<RedemptionReport> {
+        self.redeem_code_forms(code, |_| true).await
+    }
+
+    /// Redeem a code like [`Client::redeem_code`], skipping what the [`Ledger`] shows is final.
+    ///
+    /// If [`Ledger::is_settled`] shows there is nothing left to try for this account,
+    /// `None` is returned without making any request.
+    /// Otherwise, forms with a final outcome in the ledger are skipped and left out of the report.
+    /// Every outcome in the report is recorded in the ledger, including [`RedemptionOutcome::Error`]s,
+    /// which are not final, so those forms are tried again next time.
+    /// An error before any form is reached is recorded for the code as a whole.
+    /// Failing to write the ledger does not fail the redemption;
+    /// the error is kept for [`Ledger::take_write_error`] instead.
+    #[cfg(feature = "ledger")]
+    pub async fn redeem_code_with_ledger(
+        &self,
+        code: &ShiftCode,
+        ledger: &Ledger,
+    ) -> ShiftResult<Option<RedemptionReport>> {
+        let account = self.email();
+        if ledger.is_settled(code, &account) {
+            #[cfg(feature = "tracing")]
+            tracing::info!(%code, "skipping code settled in the ledger");
+            return Ok(None);
+        }
+
+        let result = self
+            .redeem_code_forms(code, |form| {
+                !ledger.is_final(code, &account, &form.service(), &form.title())
+            })
+            .await;
+        let recorded = match &result {
+            Ok(report) => ledger.record_report(&account, report),
+            Err(error) => ledger.record_error(&account, code, error),
         };
+        if let Err(error) = recorded {
+            #[cfg(feature = "tracing")]
+            tracing::error!(%error, "failed to write the ledger");
+            ledger.set_write_error(error);
+        }
 
-        Ok(Some(res))
+        result.map(Some)
+    }
+
+    /// Redeem a code for every offered service and title that `keep_form` returns true for.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "redeem_code", level = "info", skip_all, fields(%code))
+    )]
+    async fn redeem_code_forms<F>(
+        &self,
+        code: &ShiftCode,
+        keep_form: F,
+    ) -> ShiftResult<RedemptionReport>
+    where
+        F: Fn(&RewardForm) -> bool,
+    {
+        let rewards_page = self.get_rewards_page().await?;
+
+        let mut report = RedemptionReport {
+            code: code.clone(),
+            rejection: None,
+            entries: Vec::new(),
+        };
+
+        let forms = match self.get_reward_forms(&rewards_page, code).await {
+            Ok(forms) => forms,
+            Err(error) => {
+                report.rejection = Some(match error {
+                    ShiftError::ExpiredShiftCode => RedemptionOutcome::Expired,
+                    ShiftError::NonExistentShiftCode => RedemptionOutcome::NonExistent,
+                    ShiftError::UnavailableShiftCode => RedemptionOutcome::NotAvailable,
+                    error => return Err(error),
+                });
+                #[cfg(feature = "tracing")]
+                tracing::info!(rejection = ?report.rejection, "code rejected");
+                return Ok(report);
+            }
+        };
+
+        for form in forms {
+            if !keep_form(&form) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    service = %form.service(),
+                    title = %form.title(),
+                    "skipping form"
+                );
+                continue;
+            }
+
+            let outcome = match self.redeem(&form).await {
+                Ok(None) => RedemptionOutcome::Redeemed,
+                Ok(Some(json)) => RedemptionOutcome::from_json(&json),
+                Err(ShiftError::ShiftCodeAlreadyRedeemed) => RedemptionOutcome::AlreadyRedeemed,
+                Err(ShiftError::LaunchShiftGame) => RedemptionOutcome::LaunchGame,
+                Err(ShiftError::ShiftCodeRedeemFail) => RedemptionOutcome::Failed,
+                Err(ShiftError::UnrecognizedAlertNotice { text, .. }) => {
+                    RedemptionOutcome::Unknown(text)
+                }
+                // Keep the outcomes of the other forms, which may already be redeemed.
+                Err(error) => RedemptionOutcome::Error(error.to_string()),
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                service = %form.service(),
+                title = %form.title(),
+                ?outcome,
+                "redeemed form"
+            );
+
+            report.entries.push(RedemptionEntry {
+                service: form.service(),
+                title: form.title(),
+                outcome,
+            });
+        }
+
+        Ok(report)
     }
 }
 
 /// Client data
 struct ClientData {
     email: String,
-    password: String,
+    password: Option<String>,
+    /// Whether [`Client::logout`] was called since the last login, which stops logging in again
+    logged_out: bool,
+}
+
+impl std::fmt::Debug for ClientData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientData")
+            .field("email", &self.email)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("logged_out", &self.logged_out)
+            .finish()
+    }
+}
+
+/// The url a request was made to, stored in the extensions of its response
+#[derive(Debug, Clone)]
+struct RequestUrl(Url);
+
+/// The parts of a response needed to describe a parse error
+#[derive(Debug)]
+struct ResponseInfo {
+    request_url: Option<Url>,
+    url: Url,
+    status: StatusCode,
 }
 
-/// Convert a response to html, then feed it to the given transform function
-async fn res_to_html_transform<F, T>(res: reqwest::Response, f: F) -> ShiftResult<T>
-where
-    F: Fn(Html) -> ShiftResult<T> + Send + 'static,
-    T: Send + 'static,
-{
-    let text = res.error_for_status()?.text().await?;
-    let ret = tokio::task::spawn_blocking(move || f(Html::parse_document(text.as_str()))).await??;
-    Ok(ret)
+impl ResponseInfo {
+    /// The maximum length of the html excerpt of a [`PageParseError`]
+    const EXCERPT_LEN: usize = 2048;
+
+    fn new(res: &reqwest::Response) -> Self {
+        Self {
+            request_url: res
+                .extensions()
+                .get::<RequestUrl>()
+                .map(|request_url| request_url.0.clone()),
+            url: res.url().clone(),
+            status: res.status(),
+        }
+    }
+
+    /// Wrap a parse error of this response's body, dumping the body if a dump dir is given
+    fn into_parse_error(
+        self,
+        error: ShiftError,
+        text: &str,
+        dump_dir: Option<&Path>,
+    ) -> ShiftError {
+        let dump_path = dump_dir.and_then(|dir| dump_body(dir, &self.url, text).ok());
+        ShiftError::PageParse(Box::new(PageParseError {
+            request_url: self.request_url,
+            url: self.url,
+            status: self.status,
+            selector: error.selector(),
+            excerpt: html_excerpt(text, Self::EXCERPT_LEN),
+            dump_path,
+            source: error,
+        }))
+    }
+}
+
+/// The urls used by a [`Client`], derived from its base url
+#[derive(Debug)]
+struct Urls {
+    base: Url,
+    home: Url,
+    home_sign_in_failed: Url,
+    account: Url,
+    associations: Url,
+    logout: Url,
+    password_change: Url,
+    preferences: Url,
+    sessions: Url,
+    code_redemptions: Url,
+    rewards: Url,
+    entitlement_offer_codes: Url,
+}
+
+impl Urls {
+    fn new(mut base: Url) -> Self {
+        // Make sure relative urls are joined onto the base path instead of replacing its last segment.
+        if !base.path().ends_with('/') {
+            let path = format!("{}/", base.path());
+            base.set_path(&path);
+        }
+
+        let join = |path: &str| base.join(path).expect("invalid url path");
+
+        Self {
+            home: join("home"),
+            home_sign_in_failed: join("home?redirect_to=false"),
+            account: join("account"),
+            associations: join("associations"),
+            logout: join("logout"),
+            password_change: join("password/change"),
+            preferences: join("account/preferences"),
+            sessions: join("sessions"),
+            code_redemptions: join("code_redemptions"),
+            rewards: join("rewards"),
+            entitlement_offer_codes: join("entitlement_offer_codes"),
+            base,
+        }
+    }
+}
+
+/// Get the error for a response that ended up at an unexpected url.
+///
+/// Returns the interstitial error if the page is one, or `fallback` otherwise.
+async fn unexpected_page_error(res: reqwest::Response, fallback: ShiftError) -> ShiftError {
+    let url = res.url().clone();
+    let status = res.status();
+    let text = match res.text().await {
+        Ok(text) => text,
+        Err(error) => return error.into(),
+    };
+    let interstitial = Interstitial::detect(&Html::parse_document(&text), &url, status.as_u16());
+    match interstitial {
+        Some(interstitial) => ShiftError::from_interstitial(interstitial, &url),
+        None => fallback,
+    }
 }
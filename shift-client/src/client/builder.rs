@@ -0,0 +1,286 @@
+use super::{Client, ClientData, ExponentialBackoff, PollPolicy, RetryPolicy, Urls};
+use crate::{
+    error::{ShiftError, ShiftResult},
+    types::Service,
+    Session,
+};
+use reqwest::{Proxy, Url};
+use reqwest_cookie_store::{CookieStore, CookieStoreMutex};
+use shift_transport::{Live, Transport};
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+/// The default base url
+pub const DEFAULT_BASE_URL: &str = "https://shift.gearboxsoftware.com/";
+
+/// A builder for a [`Client`]
+#[derive(Debug)]
+pub struct ClientBuilder {
+    client_data: ClientData,
+    cookie_store: CookieStore,
+    from_session: bool,
+
+    base_url: Option<Url>,
+    user_agent: Option<String>,
+    timeout: Option<Duration>,
+    proxies: Vec<Proxy>,
+    reqwest_client: Option<reqwest::Client>,
+    poll_policy: PollPolicy,
+    retry_policy: Arc<dyn RetryPolicy>,
+    min_redemption_interval: Option<Duration>,
+    services: Option<Vec<Service>>,
+    auto_relogin: bool,
+    parse_error_dump_dir: Option<PathBuf>,
+    transport: Arc<dyn Transport>,
+}
+
+impl ClientBuilder {
+    /// Make a new [`ClientBuilder`] for an account that is not logged in.
+    pub fn new(email: String, password: String) -> Self {
+        Self::with_client_data(
+            ClientData {
+                email,
+                password: Some(password),
+                logged_out: false,
+            },
+            CookieStore::default(),
+            false,
+        )
+    }
+
+    /// Make a new [`ClientBuilder`] from an exported [`Session`].
+    ///
+    /// See [`Client::from_session`].
+    pub fn from_session(session: Session) -> Self {
+        let email = session.email.clone();
+        Self::with_client_data(
+            ClientData {
+                email,
+                password: None,
+                logged_out: false,
+            },
+            session.into_cookie_store(),
+            true,
+        )
+    }
+
+    fn with_client_data(
+        client_data: ClientData,
+        cookie_store: CookieStore,
+        from_session: bool,
+    ) -> Self {
+        Self {
+            client_data,
+            cookie_store,
+            from_session,
+
+            base_url: None,
+            user_agent: None,
+            timeout: None,
+            proxies: Vec::new(),
+            reqwest_client: None,
+            poll_policy: PollPolicy::default(),
+            retry_policy: Arc::new(ExponentialBackoff::default()),
+            min_redemption_interval: None,
+            services: None,
+            auto_relogin: true,
+            parse_error_dump_dir: None,
+            transport: Arc::new(Live),
+        }
+    }
+
+    /// Set the base url that every request is made against.
+    ///
+    /// Defaults to [`DEFAULT_BASE_URL`].
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Set the user agent.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the timeout for each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add a proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Use a caller-supplied [`reqwest::Client`].
+    ///
+    /// The user agent, timeout and proxies of this builder are ignored if this is set.
+    /// The given client must have a cookie store enabled for logins to work.
+    /// Since the cookies are then managed by the given client,
+    /// sessions cannot be restored into it or exported from it.
+    /// Building a client from a session with this set, or exporting a session from the built client,
+    /// returns [`ShiftError::SessionWithReqwestClient`](crate::ShiftError::SessionWithReqwestClient).
+    pub fn reqwest_client(mut self, client: reqwest::Client) -> Self {
+        self.reqwest_client = Some(client);
+        self
+    }
+
+    /// Set how the redemption status is polled after submitting a [`RewardForm`](crate::RewardForm).
+    pub fn poll_policy(mut self, poll_policy: PollPolicy) -> Self {
+        self.poll_policy = poll_policy;
+        self
+    }
+
+    /// Set the policy for retrying rate limited requests and server errors.
+    ///
+    /// Defaults to [`ExponentialBackoff`]. Use [`NoRetry`](super::NoRetry) to disable retries.
+    pub fn retry_policy(mut self, retry_policy: impl RetryPolicy + 'static) -> Self {
+        self.retry_policy = Arc::new(retry_policy);
+        self
+    }
+
+    /// Set the minimum interval between code redemption requests.
+    pub fn min_redemption_interval(mut self, interval: Duration) -> Self {
+        self.min_redemption_interval = Some(interval);
+        self
+    }
+
+    /// Only keep [`RewardForm`](crate::RewardForm)s for the given services.
+    ///
+    /// By default, forms for every service are kept.
+    pub fn services(mut self, services: impl IntoIterator<Item = Service>) -> Self {
+        self.services = Some(services.into_iter().collect());
+        self
+    }
+
+    /// Set whether the client logs in again when the session expires or the csrf token is rejected.
+    ///
+    /// This is enabled by default, and needs the password.
+    /// If disabled, [`ShiftError::SessionExpired`](crate::ShiftError::SessionExpired) is returned instead.
+    pub fn auto_relogin(mut self, auto_relogin: bool) -> Self {
+        self.auto_relogin = auto_relogin;
+        self
+    }
+
+    /// Dump the full body of every page that fails to parse into the given directory.
+    ///
+    /// The path of the dump is recorded in the [`PageParseError`](crate::PageParseError).
+    pub fn parse_error_dump_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.parse_error_dump_dir = Some(dir.into());
+        self
+    }
+
+    /// Set the [`Transport`] every request is executed with.
+    ///
+    /// Defaults to [`Live`]. Use a [`Recorder`](shift_transport::Recorder) to save the traffic to a cassette,
+    /// or a [`Replayer`](shift_transport::Replayer) to serve it from one.
+    ///
+    /// The account email, and the display and first names once logged in,
+    /// are added to the transport as secrets with [`Transport::add_secret`], so a recorder redacts them.
+    /// A [`Recorder`](shift_transport::Recorder) only redacts a name shorter than four characters
+    /// where it stands alone as a word.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Build the [`Client`].
+    ///
+    /// # Errors
+    /// Returns [`ShiftError::SessionWithReqwestClient`] if the builder was made from a session
+    /// and given a [`reqwest::Client`].
+    pub fn build(self) -> ShiftResult<Client> {
+        if self.from_session && self.reqwest_client.is_some() {
+            return Err(ShiftError::SessionWithReqwestClient);
+        }
+
+        let cookie_store = Arc::new(CookieStoreMutex::new(self.cookie_store));
+
+        let owns_cookie_store = self.reqwest_client.is_none();
+        let client = match self.reqwest_client {
+            Some(client) => client,
+            None => {
+                let mut builder = reqwest::Client::builder().cookie_provider(cookie_store.clone());
+                if let Some(user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+                builder.build()?
+            }
+        };
+
+        self.transport.add_secret(&self.client_data.email)?;
+
+        let base_url = match self.base_url {
+            Some(base_url) => base_url,
+            None => Url::parse(DEFAULT_BASE_URL).expect("invalid DEFAULT_BASE_URL"),
+        };
+
+        Ok(Client {
+            client,
+            cookie_store,
+            owns_cookie_store,
+            client_data: Arc::new(RwLock::new(self.client_data)),
+            urls: Arc::new(Urls::new(base_url)),
+            poll_policy: self.poll_policy,
+            retry_policy: self.retry_policy,
+            min_redemption_interval: self.min_redemption_interval,
+            services: self.services.map(Arc::from),
+            auto_relogin: self.auto_relogin,
+            last_redemption: Arc::default(),
+            parse_error_dump_dir: self.parse_error_dump_dir.map(Arc::from),
+            transport: self.transport,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_password() {
+        let builder = ClientBuilder::new("user@example.com".into(), "hunter2".into());
+        let debug = format!("{builder:?}");
+        assert!(debug.contains("user@example.com"));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    #[test]
+    fn session_with_reqwest_client() {
+        let session = Client::new("user@example.com".into(), "hunter2".into())
+            .session()
+            .unwrap();
+        let error = ClientBuilder::from_session(session)
+            .reqwest_client(reqwest::Client::new())
+            .build()
+            .err()
+            .expect("building should fail");
+        assert!(
+            matches!(error, ShiftError::SessionWithReqwestClient),
+            "{error:?}"
+        );
+
+        let error = Client::builder("user@example.com".into(), "hunter2".into())
+            .reqwest_client(reqwest::Client::new())
+            .build()
+            .unwrap()
+            .session()
+            .unwrap_err();
+        assert!(
+            matches!(error, ShiftError::SessionWithReqwestClient),
+            "{error:?}"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+/// How [`Client::redeem`](crate::Client::redeem) polls the redemption status.
+///
+/// The status is checked right away.
+/// While the redemption is still in progress,
+/// the client waits before checking again, growing the delay by `backoff` each time.
+#[derive(Debug, Clone, Copy)]
+pub struct PollPolicy {
+    /// The delay after the first status check
+    pub initial_delay: Duration,
+
+    /// The factor the delay is multiplied by after each status check
+    pub backoff: f64,
+
+    /// The maximum delay between status checks
+    pub max_delay: Duration,
+
+    /// The maximum number of status checks
+    pub max_attempts: u32,
+
+    /// The maximum total time to poll for
+    pub deadline: Option<Duration>,
+}
+
+impl PollPolicy {
+    /// Get the delay to use after the given delay.
+    ///
+    /// A delay too large to represent is capped at `max_delay`.
+    pub(crate) fn next_delay(&self, delay: Duration) -> Duration {
+        Duration::try_from_secs_f64(delay.as_secs_f64() * self.backoff.max(1.0))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+impl Default for PollPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(2),
+            backoff: 1.5,
+            max_delay: Duration::from_secs(10),
+            max_attempts: 20,
+            deadline: Some(Duration::from_secs(120)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn next_delay() {
+        let policy = PollPolicy::default();
+        assert_eq!(
+            policy.next_delay(Duration::from_secs(2)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            policy.next_delay(Duration::from_secs(8)),
+            Duration::from_secs(10)
+        );
+
+        let policy = PollPolicy {
+            backoff: f64::INFINITY,
+            max_delay: Duration::MAX,
+            ..PollPolicy::default()
+        };
+        assert_eq!(policy.next_delay(Duration::from_secs(2)), Duration::MAX);
+    }
+}
@@ -0,0 +1,176 @@
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+use time::{format_description::well_known::Rfc2822, OffsetDateTime};
+
+/// A policy deciding whether a request that was rate limited or hit a server error is retried.
+///
+/// Requests are retried on `429 Too Many Requests` responses,
+/// and on `5xx` responses to idempotent requests.
+/// A `5xx` response to a `POST` is never retried,
+/// since the server may have processed it already, like a code redemption or a sign in.
+pub trait RetryPolicy: std::fmt::Debug + Send + Sync {
+    /// Get the delay before the next retry, or `None` to give up.
+    ///
+    /// `retries` is the number of retries already made for this request.
+    /// `retry_after` is the delay requested by the server through the `Retry-After` header, if any.
+    fn retry_delay(
+        &self,
+        retries: u32,
+        status: StatusCode,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration>;
+}
+
+/// A [`RetryPolicy`] that never retries
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRetry;
+
+impl RetryPolicy for NoRetry {
+    fn retry_delay(
+        &self,
+        _retries: u32,
+        _status: StatusCode,
+        _retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        None
+    }
+}
+
+/// A [`RetryPolicy`] that backs off exponentially, honoring `Retry-After`.
+///
+/// If the server asks to wait longer than `max_delay`, it gives up instead,
+/// so a rate limited request returns [`ShiftError::RateLimited`](crate::ShiftError::RateLimited)
+/// with the requested delay rather than sleeping for it.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialBackoff {
+    /// The maximum number of retries
+    pub max_retries: u32,
+
+    /// The delay before the first retry
+    pub base_delay: Duration,
+
+    /// The maximum delay before a retry
+    pub max_delay: Duration,
+
+    /// Whether to add random jitter to each delay
+    pub jitter: bool,
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn retry_delay(
+        &self,
+        retries: u32,
+        _status: StatusCode,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if retries >= self.max_retries {
+            return None;
+        }
+
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+
+        let delay = self
+            .base_delay
+            .saturating_mul(2_u32.saturating_pow(retries))
+            .min(self.max_delay);
+        if !self.jitter {
+            return Some(delay);
+        }
+
+        // Full jitter in the upper half, so that the delay still grows with each retry.
+        let half = delay / 2;
+        Some(half + half.mul_f64(fastrand::f64()))
+    }
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(60),
+            jitter: true,
+        }
+    }
+}
+
+/// Check whether a response with the given status may be retried for a request with the given method.
+pub(crate) fn is_retryable(method: &Method, status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (status.is_server_error() && method.is_idempotent())
+}
+
+/// Parse a `Retry-After` header, given in seconds or as an HTTP date.
+///
+/// A date in the past is a delay of zero.
+pub(crate) fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = OffsetDateTime::parse(value, &Rfc2822).ok()?;
+    let delay = date - OffsetDateTime::now_utc();
+    Some(delay.try_into().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = ExponentialBackoff {
+            jitter: false,
+            ..ExponentialBackoff::default()
+        };
+        let status = StatusCode::TOO_MANY_REQUESTS;
+
+        assert_eq!(
+            policy.retry_delay(0, status, None),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.retry_delay(2, status, None),
+            Some(Duration::from_secs(8))
+        );
+        assert_eq!(
+            policy.retry_delay(1, status, Some(Duration::from_secs(30))),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            policy.retry_delay(1, status, Some(Duration::from_secs(86400))),
+            None
+        );
+        assert_eq!(policy.retry_delay(3, status, None), None);
+    }
+
+    #[test]
+    fn retry_after() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            "Sun, 06 Nov 1994 08:49:37 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(reqwest::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn retryable() {
+        assert!(is_retryable(&Method::GET, StatusCode::BAD_GATEWAY));
+        assert!(is_retryable(&Method::POST, StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(&Method::POST, StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(&Method::GET, StatusCode::NOT_FOUND));
+    }
+}
@@ -1,3 +1,10 @@
+#[cfg(feature = "client")]
+use crate::types::Interstitial;
+#[cfg(feature = "client")]
+use reqwest::{StatusCode, Url};
+#[cfg(feature = "client")]
+use std::path::PathBuf;
+
 /// Library result type
 pub type ShiftResult<T> = Result<T, ShiftError>;
 
@@ -7,11 +14,15 @@ pub type RewardFormError = crate::types::rewards::reward_form::FromHtmlError;
 pub type InvalidCodeRedemptionPageError =
     crate::types::rewards::code_redemption_page::FromHtmlError;
 pub type InvalidAccountPage = crate::types::account_page::FromHtmlError;
+pub type InvalidAssociationsPage = crate::types::associations_page::FromHtmlError;
+pub type InvalidAccountPreferencesPage = crate::types::account_preferences_page::FromHtmlError;
+pub type InvalidPasswordChangePage = crate::types::password_change_page::FromHtmlError;
 
 /// The library error type
 #[derive(Debug, thiserror::Error)]
 pub enum ShiftError {
     /// Reqwest HTTP error
+    #[cfg(feature = "client")]
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
@@ -19,14 +30,70 @@ pub enum ShiftError {
     #[error("incorrect email or password")]
     IncorrectEmailOrPassword,
 
+    /// The client has no password, so it cannot log in
+    #[error("missing password")]
+    MissingPassword,
+
+    /// A session was restored into, or exported from, a client using a caller-supplied [`reqwest::Client`].
+    ///
+    /// The given client manages its own cookies, so the session would be lost or empty.
+    #[cfg(feature = "client")]
+    #[error("sessions cannot be restored into or exported from a caller-supplied reqwest client")]
+    SessionWithReqwestClient,
+
+    /// The session has expired or belongs to a different account,
+    /// and the client could not log in again
+    #[error("session expired")]
+    SessionExpired,
+
+    /// The site is down for maintenance
+    #[error("shift is down for maintenance (at '{url}')")]
+    Maintenance {
+        /// The url of the maintenance page
+        url: String,
+    },
+
+    /// A captcha or bot challenge was served instead of the requested page
+    #[error("a captcha must be solved (at '{url}')")]
+    CaptchaRequired {
+        /// The url of the challenge page
+        url: String,
+    },
+
+    /// The account email must be verified before continuing
+    #[error("the account email must be verified (at '{url}')")]
+    EmailVerificationRequired {
+        /// The url of the verification page
+        url: String,
+    },
+
+    /// The account is locked
+    #[error("the account is locked (at '{url}')")]
+    AccountLocked {
+        /// The url of the lockout page
+        url: String,
+    },
+
     /// Invalid HTTP Redirect
     #[error("invalid http redirect '{0}'")]
     InvalidRedirect(String),
 
+    /// The server kept rate limiting requests after every retry
+    #[error("rate limited")]
+    RateLimited {
+        /// The delay requested by the server, if any
+        retry_after: Option<std::time::Duration>,
+    },
+
     /// Json Error
     #[error(transparent)]
     Json(#[from] serde_json::Error),
 
+    /// A page failed to parse
+    #[cfg(feature = "client")]
+    #[error(transparent)]
+    PageParse(#[from] Box<PageParseError>),
+
     /// Invalid Rewards page
     #[error("invalid rewards page")]
     InvalidRewardsPage(#[from] RewardsPageError),
@@ -42,11 +109,49 @@ pub enum ShiftError {
     /// Invalid Account page
     #[error("invalid account page")]
     InvalidAccountPage(#[from] InvalidAccountPage),
+    /// Invalid Associations page
+    #[error("invalid associations page")]
+    InvalidAssociationsPage(#[from] InvalidAssociationsPage),
+    /// Invalid Account preferences page
+    #[error("invalid account preferences page")]
+    InvalidAccountPreferencesPage(#[from] InvalidAccountPreferencesPage),
+    /// Invalid Password change page
+    #[error("invalid password change page")]
+    InvalidPasswordChangePage(#[from] InvalidPasswordChangePage),
+
+    /// The password was not changed
+    #[error("failed to change password")]
+    PasswordChangeFailed,
+
+    /// The profile was not updated
+    #[error("failed to update profile")]
+    ProfileUpdateFailed,
+
+    /// Some preferences did not keep the submitted value
+    #[error("failed to update preferences {names:?}")]
+    PreferencesUpdateFailed {
+        /// The input names of the preferences that were not updated
+        names: Vec<String>,
+    },
+
+    /// The account page has no profile form
+    #[error("missing profile form")]
+    MissingProfileForm,
 
     /// Missing alert notice
     #[error("missing alert notice")]
     MissingAlertNotice,
 
+    /// An alert notice that was not recognized
+    #[error("unrecognized alert notice '{text}'")]
+    UnrecognizedAlertNotice {
+        /// The text of the alert
+        text: String,
+
+        /// The level of the alert
+        kind: crate::types::rewards::AlertKind,
+    },
+
     /// NonExistentShiftCode
     #[error("non-existent shift code")]
     NonExistentShiftCode,
@@ -67,7 +172,94 @@ pub enum ShiftError {
     #[error("failed to redeem shift code")]
     ShiftCodeRedeemFail,
 
+    /// The redemption was still in progress when the [`PollPolicy`](crate::client::PollPolicy) ran out
+    #[error("timed out waiting for the redemption status")]
+    RedemptionStatusTimeout {
+        /// The last redemption status seen
+        last: Box<crate::types::CodeRedemptionJson>,
+    },
+
+    /// Failed to read or replay a cassette
+    #[cfg(feature = "client")]
+    #[error(transparent)]
+    Transport(shift_transport::TransportError),
+
+    /// Failed to read or write the ledger
+    #[cfg(feature = "ledger")]
+    #[error(transparent)]
+    Ledger(#[from] crate::ledger::LedgerError),
+
     /// Failed to join tokio task
+    #[cfg(feature = "client")]
     #[error("tokio task join error")]
     TokioJoin(#[from] tokio::task::JoinError),
 }
+
+impl ShiftError {
+    /// The selector that did not match, if this is a page parse error
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "client")]
+            Self::PageParse(error) => error.selector,
+            Self::InvalidRewardsPage(error) => error.selector(),
+            Self::InvalidHomePage(error) => error.selector(),
+            Self::InvalidRewardForm(error) => error.selector(),
+            Self::InvalidCodeRedemptionPage(error) => error.selector(),
+            Self::InvalidAccountPage(error) => error.selector(),
+            Self::InvalidAssociationsPage(error) => error.selector(),
+            Self::InvalidAccountPreferencesPage(error) => error.selector(),
+            Self::InvalidPasswordChangePage(error) => error.selector(),
+            _ => None,
+        }
+    }
+
+    /// Make the error for an [`Interstitial`] served at the given url
+    #[cfg(feature = "client")]
+    pub(crate) fn from_interstitial(interstitial: Interstitial, url: &Url) -> Self {
+        let url = url.as_str().to_string();
+        match interstitial {
+            Interstitial::Maintenance => Self::Maintenance { url },
+            Interstitial::Captcha => Self::CaptchaRequired { url },
+            Interstitial::EmailVerification => Self::EmailVerificationRequired { url },
+            Interstitial::AccountLocked => Self::AccountLocked { url },
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl From<shift_transport::TransportError> for ShiftError {
+    fn from(error: shift_transport::TransportError) -> Self {
+        match error {
+            shift_transport::TransportError::Reqwest(error) => Self::Reqwest(error),
+            error => Self::Transport(error),
+        }
+    }
+}
+
+/// A page that failed to parse, with the response it came from
+#[cfg(feature = "client")]
+#[derive(Debug, thiserror::Error)]
+#[error("failed to parse page '{url}' ({status})")]
+pub struct PageParseError {
+    /// The url that was requested
+    pub request_url: Option<Url>,
+
+    /// The url of the page, after redirects
+    pub url: Url,
+
+    /// The HTTP status
+    pub status: StatusCode,
+
+    /// The selector that did not match, if known
+    pub selector: Option<&'static str>,
+
+    /// A bounded excerpt of the html
+    pub excerpt: String,
+
+    /// The file the full body was dumped to, if dumping is enabled
+    pub dump_path: Option<PathBuf>,
+
+    /// The parse error
+    #[source]
+    pub source: ShiftError,
+}
@@ -0,0 +1,546 @@
+use crate::types::{RedemptionOutcome, RedemptionReport, Service, ShiftCode, Title};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    path::Path,
+    sync::Mutex,
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// Ledger error
+#[derive(Debug, thiserror::Error)]
+pub enum LedgerError {
+    /// Failed to read or write the ledger file
+    #[error("failed to access ledger")]
+    Io(#[from] std::io::Error),
+
+    /// A line of the ledger file is not a valid entry
+    #[error("invalid ledger entry on line {line}")]
+    InvalidEntry {
+        /// The line number, starting at 1
+        line: usize,
+
+        /// The error
+        #[source]
+        error: serde_json::Error,
+    },
+
+    /// Failed to serialize an entry
+    #[error("failed to serialize ledger entry")]
+    Json(#[from] serde_json::Error),
+
+    /// Failed to format a timestamp
+    #[error("failed to format timestamp")]
+    Format(#[from] time::error::Format),
+}
+
+/// The result of a redemption attempt recorded in a [`Ledger`]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptResult {
+    /// The attempt finished with an outcome
+    Outcome(RedemptionOutcome),
+
+    /// The attempt failed with an error before an outcome was known
+    Error(String),
+}
+
+/// A redemption attempt recorded in a [`Ledger`]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LedgerEntry {
+    /// The code
+    pub code: ShiftCode,
+
+    /// The email of the account
+    pub account: String,
+
+    /// The service, unset if the attempt ended before any service was offered
+    pub service: Option<Service>,
+
+    /// The title, unset if the attempt ended before any title was offered
+    pub title: Option<Title>,
+
+    /// The result
+    pub result: AttemptResult,
+
+    /// When the attempt was made
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+impl LedgerEntry {
+    /// Check whether this entry has a final outcome, so the attempt does not need to be repeated.
+    ///
+    /// See [`RedemptionOutcome::is_final`].
+    pub fn is_final(&self) -> bool {
+        matches!(&self.result, AttemptResult::Outcome(outcome) if outcome.is_final())
+    }
+}
+
+/// A filter for [`Ledger`] entries. Unset fields match every entry.
+#[derive(Debug, Clone, Default)]
+pub struct LedgerQuery {
+    /// Only match entries for this code
+    pub code: Option<ShiftCode>,
+
+    /// Only match entries for this account
+    pub account: Option<String>,
+
+    /// Only match entries for this service
+    pub service: Option<Service>,
+
+    /// Only match entries for this title
+    pub title: Option<Title>,
+
+    /// Only match entries with a final outcome
+    pub final_only: bool,
+
+    /// Only match entries made at or after this time
+    pub since: Option<OffsetDateTime>,
+}
+
+impl LedgerQuery {
+    /// Check whether an entry matches this query
+    pub fn matches(&self, entry: &LedgerEntry) -> bool {
+        self.code.as_ref().is_none_or(|code| *code == entry.code)
+            && self
+                .account
+                .as_deref()
+                .is_none_or(|account| account.eq_ignore_ascii_case(&entry.account))
+            && self
+                .service
+                .as_ref()
+                .is_none_or(|service| Some(service) == entry.service.as_ref())
+            && self
+                .title
+                .as_ref()
+                .is_none_or(|title| Some(title) == entry.title.as_ref())
+            && (!self.final_only || entry.is_final())
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+/// The format of a [`Ledger`] export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A json array of entries
+    Json,
+
+    /// One json entry per line, like the ledger file
+    JsonLines,
+
+    /// Csv, with a header row
+    Csv,
+}
+
+/// A record of every redemption attempt, used to avoid submitting codes again.
+///
+/// A ledger opened from a file keeps it as json lines, appending each entry as it is recorded.
+#[derive(Debug)]
+pub struct Ledger {
+    state: Mutex<LedgerState>,
+}
+
+#[derive(Debug)]
+struct LedgerState {
+    entries: Vec<LedgerEntry>,
+    file: Option<File>,
+    write_error: Option<LedgerError>,
+}
+
+impl Ledger {
+    /// Open the ledger at the given path, creating it if it does not exist.
+    ///
+    /// A last line cut off by a crash while it was written is dropped from the file.
+    /// Any other invalid line is an error.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LedgerError> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+
+        let mut data = String::new();
+        file.read_to_string(&mut data)?;
+
+        let mut entries = Vec::new();
+        let mut valid_len = 0;
+        for (i, line) in data.split_inclusive('\n').enumerate() {
+            let is_last = valid_len + line.len() == data.len();
+            if !line.trim().is_empty() {
+                match serde_json::from_str(line) {
+                    Ok(entry) => entries.push(entry),
+                    Err(_) if is_last && !line.ends_with('\n') => break,
+                    Err(error) => return Err(LedgerError::InvalidEntry { line: i + 1, error }),
+                }
+            }
+            valid_len += line.len();
+        }
+
+        if valid_len < data.len() {
+            file.set_len(valid_len as u64)?;
+        } else if !data.is_empty() && !data.ends_with('\n') {
+            file.write_all(b"\n")?;
+        }
+
+        Ok(Self {
+            state: Mutex::new(LedgerState {
+                entries,
+                file: Some(file),
+                write_error: None,
+            }),
+        })
+    }
+
+    /// Make a ledger that is only kept in memory
+    pub fn in_memory() -> Self {
+        Self {
+            state: Mutex::new(LedgerState {
+                entries: Vec::new(),
+                file: None,
+                write_error: None,
+            }),
+        }
+    }
+
+    /// Record an entry.
+    ///
+    /// The entry is kept in memory even if writing it to the file fails.
+    pub fn record(&self, entry: LedgerEntry) -> Result<(), LedgerError> {
+        let mut state = self.state.lock().expect("ledger poisoned");
+        let line = serde_json::to_vec(&entry);
+        state.entries.push(entry);
+        if let Some(file) = state.file.as_mut() {
+            let mut line = line?;
+            line.push(b'\n');
+            file.write_all(&line)?;
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Keep an error from writing to the ledger for [`Ledger::take_write_error`]
+    #[cfg(feature = "client")]
+    pub(crate) fn set_write_error(&self, error: LedgerError) {
+        self.state.lock().expect("ledger poisoned").write_error = Some(error);
+    }
+
+    /// Take the last error from writing to the ledger during a redemption, if any.
+    ///
+    /// [`Client::redeem_code_with_ledger`](crate::Client::redeem_code_with_ledger) does not fail
+    /// a redemption that went through when the ledger cannot be written, so its errors are kept here.
+    pub fn take_write_error(&self) -> Option<LedgerError> {
+        self.state
+            .lock()
+            .expect("ledger poisoned")
+            .write_error
+            .take()
+    }
+
+    /// Record every outcome of a [`RedemptionReport`] for the given account
+    pub fn record_report(
+        &self,
+        account: &str,
+        report: &RedemptionReport,
+    ) -> Result<(), LedgerError> {
+        let timestamp = OffsetDateTime::now_utc();
+        let make_entry = |service, title, outcome: &RedemptionOutcome| LedgerEntry {
+            code: report.code.clone(),
+            account: account.to_string(),
+            service,
+            title,
+            result: AttemptResult::Outcome(outcome.clone()),
+            timestamp,
+        };
+
+        // Record every outcome in memory, even after a failed write.
+        let mut ret = Ok(());
+        if let Some(rejection) = report.rejection.as_ref() {
+            ret = ret.and(self.record(make_entry(None, None, rejection)));
+        }
+        for entry in report.entries.iter() {
+            ret = ret.and(self.record(make_entry(
+                Some(entry.service.clone()),
+                Some(entry.title.clone()),
+                &entry.outcome,
+            )));
+        }
+
+        ret
+    }
+
+    /// Record an attempt on the given account that failed with an error
+    pub fn record_error(
+        &self,
+        account: &str,
+        code: &ShiftCode,
+        error: &dyn std::error::Error,
+    ) -> Result<(), LedgerError> {
+        self.record(LedgerEntry {
+            code: code.clone(),
+            account: account.to_string(),
+            service: None,
+            title: None,
+            result: AttemptResult::Error(error.to_string()),
+            timestamp: OffsetDateTime::now_utc(),
+        })
+    }
+
+    /// Get every entry, in the order they were recorded
+    pub fn entries(&self) -> Vec<LedgerEntry> {
+        self.state.lock().expect("ledger poisoned").entries.clone()
+    }
+
+    /// Get the entries matching a query, in the order they were recorded
+    pub fn query(&self, query: &LedgerQuery) -> Vec<LedgerEntry> {
+        self.state
+            .lock()
+            .expect("ledger poisoned")
+            .entries
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .cloned()
+            .collect()
+    }
+
+    /// Check whether a code has a final outcome for the given account, service and title
+    pub fn is_final(
+        &self,
+        code: &ShiftCode,
+        account: &str,
+        service: &Service,
+        title: &Title,
+    ) -> bool {
+        let query = LedgerQuery {
+            code: Some(code.clone()),
+            account: Some(account.to_string()),
+            service: Some(service.clone()),
+            title: Some(title.clone()),
+            final_only: true,
+            ..LedgerQuery::default()
+        };
+        let state = self.state.lock().expect("ledger poisoned");
+        state.entries.iter().any(|entry| query.matches(entry))
+    }
+
+    /// Check whether there is nothing left to try for a code on the given account.
+    ///
+    /// This is the case if the code expired or does not exist,
+    /// or if every service and title recorded for the account has a final outcome.
+    pub fn is_settled(&self, code: &ShiftCode, account: &str) -> bool {
+        let state = self.state.lock().expect("ledger poisoned");
+        let entries = state.entries.iter().filter(|entry| entry.code == *code);
+
+        let mut forms: HashMap<(&Service, &Title), bool> = HashMap::new();
+        for entry in entries {
+            if let AttemptResult::Outcome(
+                RedemptionOutcome::Expired | RedemptionOutcome::NonExistent,
+            ) = entry.result
+            {
+                return true;
+            }
+
+            if !entry.account.eq_ignore_ascii_case(account) {
+                continue;
+            }
+            if let (Some(service), Some(title)) = (entry.service.as_ref(), entry.title.as_ref()) {
+                *forms.entry((service, title)).or_default() |= entry.is_final();
+            }
+        }
+
+        !forms.is_empty() && forms.values().all(|is_final| *is_final)
+    }
+
+    /// Export the entries matching a query
+    pub fn export(
+        &self,
+        mut writer: impl Write,
+        query: &LedgerQuery,
+        format: ExportFormat,
+    ) -> Result<(), LedgerError> {
+        let entries = self.query(query);
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_writer_pretty(&mut writer, &entries)?;
+                writeln!(writer)?;
+            }
+            ExportFormat::JsonLines => {
+                for entry in entries.iter() {
+                    serde_json::to_writer(&mut writer, entry)?;
+                    writeln!(writer)?;
+                }
+            }
+            ExportFormat::Csv => {
+                writeln!(writer, "timestamp,account,code,service,title,result,detail")?;
+                for entry in entries.iter() {
+                    let (result, detail) = match &entry.result {
+                        AttemptResult::Outcome(outcome) => {
+                            (outcome_name(outcome), outcome_detail(outcome))
+                        }
+                        AttemptResult::Error(error) => ("error", error.as_str()),
+                    };
+                    let fields = [
+                        entry.timestamp.format(&Rfc3339)?,
+                        entry.account.clone(),
+                        entry.code.to_string(),
+                        entry
+                            .service
+                            .as_ref()
+                            .map(ToString::to_string)
+                            .unwrap_or_default(),
+                        entry
+                            .title
+                            .as_ref()
+                            .map(ToString::to_string)
+                            .unwrap_or_default(),
+                        result.to_string(),
+                        detail.to_string(),
+                    ];
+                    let row: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+                    writeln!(writer, "{}", row.join(","))?;
+                }
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Get the name of an outcome, as it is serialized
+fn outcome_name(outcome: &RedemptionOutcome) -> &'static str {
+    match outcome {
+        RedemptionOutcome::Redeemed => "redeemed",
+        RedemptionOutcome::AlreadyRedeemed => "already_redeemed",
+        RedemptionOutcome::Expired => "expired",
+        RedemptionOutcome::NonExistent => "non_existent",
+        RedemptionOutcome::NotAvailable => "not_available",
+        RedemptionOutcome::LaunchGame => "launch_game",
+        RedemptionOutcome::Failed => "failed",
+        RedemptionOutcome::Unknown(_) => "unknown",
+        RedemptionOutcome::Error(_) => "error",
+    }
+}
+
+/// Get the text of an unknown or error outcome
+fn outcome_detail(outcome: &RedemptionOutcome) -> &str {
+    match outcome {
+        RedemptionOutcome::Unknown(text) | RedemptionOutcome::Error(text) => text,
+        _ => "",
+    }
+}
+
+/// Quote a csv field if needed
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::RedemptionEntry;
+
+    const CODE: &str = "KJKBT-KCBJC-FXT65-TTTT3-W3XSR";
+
+    fn report(outcome: RedemptionOutcome) -> RedemptionReport {
+        RedemptionReport {
+            code: CODE.parse().unwrap(),
+            rejection: None,
+            entries: vec![RedemptionEntry {
+                service: Service::Steam,
+                title: Title::Borderlands2,
+                outcome,
+            }],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("shift-client-ledger-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let code: ShiftCode = CODE.parse().unwrap();
+
+        let ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_report("user@example.com", &report(RedemptionOutcome::LaunchGame))
+            .unwrap();
+        assert!(!ledger.is_settled(&code, "user@example.com"));
+        ledger
+            .record_report("user@example.com", &report(RedemptionOutcome::Redeemed))
+            .unwrap();
+        assert!(ledger.is_settled(&code, "user@example.com"));
+        assert!(!ledger.is_settled(&code, "other@example.com"));
+        drop(ledger);
+
+        let ledger = Ledger::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ledger.entries().len(), 2);
+        assert!(ledger.is_final(
+            &code,
+            "USER@example.com",
+            &Service::Steam,
+            &Title::Borderlands2
+        ));
+
+        let query = LedgerQuery {
+            final_only: true,
+            ..LedgerQuery::default()
+        };
+        let mut csv = Vec::new();
+        ledger.export(&mut csv, &query, ExportFormat::Csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.contains(",user@example.com,KJKBT-KCBJC-FXT65-TTTT3-W3XSR,steam,"));
+        assert!(csv.contains(",redeemed,"));
+    }
+
+    #[test]
+    fn torn_last_line() {
+        let path = std::env::temp_dir().join(format!(
+            "shift-client-ledger-torn-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_report("user@example.com", &report(RedemptionOutcome::Redeemed))
+            .unwrap();
+        drop(ledger);
+        let data = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("{data}{}", &data[..data.len() / 2])).unwrap();
+
+        let ledger = Ledger::open(&path).unwrap();
+        assert_eq!(ledger.entries().len(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), data);
+        ledger
+            .record_report("other@example.com", &report(RedemptionOutcome::Redeemed))
+            .unwrap();
+        drop(ledger);
+        assert_eq!(Ledger::open(&path).unwrap().entries().len(), 2);
+
+        // A broken line before the end is not from a crash.
+        std::fs::write(&path, format!("{}\n{data}", &data[..data.len() / 2])).unwrap();
+        let error = Ledger::open(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(error, LedgerError::InvalidEntry { line: 1, .. }));
+    }
+
+    #[test]
+    fn expired_codes_are_settled() {
+        let ledger = Ledger::in_memory();
+        let mut report = report(RedemptionOutcome::Redeemed);
+        report.entries.clear();
+        report.rejection = Some(RedemptionOutcome::Expired);
+        ledger.record_report("user@example.com", &report).unwrap();
+
+        assert!(ledger.is_settled(&report.code, "other@example.com"));
+    }
+}
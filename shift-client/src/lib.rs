@@ -1,6 +1,30 @@
+#[cfg(feature = "client")]
+pub mod account_pool;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "client")]
 pub mod client;
 pub mod error;
+#[cfg(feature = "ledger")]
+pub mod ledger;
+#[cfg(feature = "client")]
+pub mod session;
 pub mod types;
 pub(crate) mod util;
 
-pub use crate::{client::Client, error::ShiftError, types::RewardForm};
+#[cfg(feature = "client")]
+pub use shift_transport as transport;
+
+#[cfg(feature = "ledger")]
+pub use crate::ledger::Ledger;
+#[cfg(feature = "client")]
+pub use crate::{
+    account_pool::AccountPool,
+    client::{Client, ClientBuilder},
+    error::PageParseError,
+    session::Session,
+};
+pub use crate::{
+    error::ShiftError,
+    types::{extract_codes, RewardForm, ShiftCode},
+};
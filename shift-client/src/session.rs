@@ -0,0 +1,77 @@
+use cookie_store::{Cookie, CookieStore};
+
+/// An exported, authenticated session.
+///
+/// This can be serialized and used to rebuild a [`Client`](crate::Client) later,
+/// without logging in again.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Session {
+    /// The email of the account this session belongs to
+    pub email: String,
+
+    /// The session cookies
+    cookies: Vec<Cookie<'static>>,
+}
+
+impl Session {
+    /// Make a [`Session`] from a cookie store.
+    pub(crate) fn from_cookie_store(email: String, cookie_store: &CookieStore) -> Self {
+        Self {
+            email,
+            cookies: cookie_store.iter_unexpired().cloned().collect(),
+        }
+    }
+
+    /// Make a cookie store from this [`Session`].
+    ///
+    /// Expired cookies are dropped.
+    pub(crate) fn into_cookie_store(self) -> CookieStore {
+        CookieStore::from_cookies(
+            self.cookies
+                .into_iter()
+                .map(Ok::<_, std::convert::Infallible>),
+            false,
+        )
+        .expect("infallible")
+    }
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cookies: Vec<_> = self
+            .cookies
+            .iter()
+            .map(|cookie| format!("{}=<redacted>", cookie.name()))
+            .collect();
+        f.debug_struct("Session")
+            .field("email", &self.email)
+            .field("cookies", &cookies)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let url = reqwest::Url::parse("https://shift.gearboxsoftware.com/account").unwrap();
+        let mut cookie_store = CookieStore::default();
+        cookie_store
+            .parse("_session_id=abc123; path=/; HttpOnly", &url)
+            .expect("failed to parse cookie");
+
+        let session = Session::from_cookie_store("test@example.com".into(), &cookie_store);
+        assert!(!format!("{session:?}").contains("abc123"));
+        let json = serde_json::to_string(&session).expect("failed to serialize session");
+        let session: Session = serde_json::from_str(&json).expect("failed to deserialize session");
+
+        assert_eq!(session.email, "test@example.com");
+        let cookie_store = session.into_cookie_store();
+        let cookie = cookie_store
+            .get("shift.gearboxsoftware.com", "/", "_session_id")
+            .expect("missing session cookie");
+        assert_eq!(cookie.value(), "abc123");
+    }
+}
@@ -1,9 +1,32 @@
+//! The pages and other types of the SHiFT site.
+//!
+//! Each page type can be parsed from an html string without the network, so archived pages can be parsed too.
+//!
+//! # Unverified
+//! [`AccountPreferencesPage`], [`AssociationsPage`], [`PasswordChangePage`] and the [`Interstitial`] markers
+//! were written against hand-made pages, not captures of the live site,
+//! so they may fail against the real pages until they are checked.
+
 pub mod account_page;
+pub mod account_preferences_page;
+pub mod associations_page;
 pub mod home_page;
+pub mod interstitial;
+pub mod password_change_page;
+pub mod redemption_report;
 pub mod rewards;
+pub mod service;
+pub mod shift_code;
 
 pub use self::{
-    account_page::AccountPage,
+    account_page::{AccountPage, ProfileUpdate},
+    account_preferences_page::{AccountPreferencesPage, Preference},
+    associations_page::{Association, AssociationsPage},
     home_page::HomePage,
+    interstitial::Interstitial,
+    password_change_page::PasswordChangePage,
+    redemption_report::{RedemptionEntry, RedemptionOutcome, RedemptionReport},
     rewards::{CodeRedemptionJson, CodeRedemptionPage, RewardForm, RewardsPage},
+    service::{Service, Title},
+    shift_code::{extract_codes, ExtractedCode, ParseShiftCodeError, ShiftCode},
 };
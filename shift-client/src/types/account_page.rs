@@ -1,6 +1,11 @@
-use crate::util::extract_csrf_token;
+use crate::util::{extract_csrf_token, CSRF_TOKEN_SELECTOR};
+use once_cell::sync::Lazy;
 use scraper::{Html, Selector};
 
+static EDIT_USER_FORM_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("form.edit_user[action]").expect("invalid EDIT_USER_FORM_SELECTOR")
+});
+
 /// Error that may occur while parsing an [`AccountPage`].
 #[derive(Debug, thiserror::Error)]
 pub enum FromHtmlError {
@@ -18,10 +23,24 @@ pub enum FromHtmlError {
     MissingFirstName,
 }
 
+impl FromHtmlError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingCsrfToken => Some(CSRF_TOKEN_SELECTOR),
+            Self::MissingEmail => Some("#current_email"),
+            Self::MissingDisplayName => Some("#current_display_name"),
+            Self::MissingFirstName => Some("#current_first_name"),
+        }
+    }
+}
+
 /// The account page
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AccountPage {
     /// The csrf token
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub csrf_token: String,
     /// The email
     pub email: String,
@@ -29,9 +48,17 @@ pub struct AccountPage {
     pub display_name: String,
     /// The first name
     pub first_name: String,
+
+    /// The url the profile form is submitted to
+    pub profile_form_action: Option<String>,
 }
 
 impl AccountPage {
+    /// Parse an [`AccountPage`] from an html string
+    pub fn parse(html: &str) -> Result<Self, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html))
+    }
+
     /// Parse an [`AccountPage`] from html
     pub(crate) fn from_html(html: &Html) -> Result<Self, FromHtmlError> {
         let csrf_token = extract_csrf_token(html)
@@ -50,15 +77,40 @@ impl AccountPage {
             .ok_or(FromHtmlError::MissingFirstName)?
             .to_string();
 
+        let profile_form_action = html
+            .select(&EDIT_USER_FORM_SELECTOR)
+            .next()
+            .and_then(|form| form.value().attr("action"))
+            .map(String::from);
+
         Ok(Self {
             csrf_token,
             email,
             display_name,
             first_name,
+            profile_form_action,
         })
     }
 }
 
+/// Changes to the profile, submitted with [`Client::update_profile`](crate::Client::update_profile).
+///
+/// Fields that are `None` are left unchanged.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProfileUpdate {
+    /// The new email.
+    ///
+    /// Email changes must be confirmed through the new email before they take effect.
+    pub email: Option<String>,
+
+    /// The new display name
+    pub display_name: Option<String>,
+
+    /// The new first name
+    pub first_name: Option<String>,
+}
+
 fn get_text_by_id<'a>(html: &'a Html, id: &str) -> Option<&'a str> {
     let selector = Selector::parse(&format!("#{id}")).ok()?;
     let element = html.select(&selector).next()?;
@@ -75,6 +127,7 @@ mod test {
     fn sample_1() {
         let html = Html::parse_document(SAMPLE_1);
         let page = AccountPage::from_html(&html).expect("invalid account page");
+        assert_eq!(page.profile_form_action.as_deref(), Some("/users/13114556"));
         dbg!(page);
     }
 }
@@ -0,0 +1,246 @@
+use crate::util::{extract_csrf_token, extract_hidden_fields, CSRF_TOKEN_SELECTOR};
+use once_cell::sync::Lazy;
+use scraper::{ElementRef, Html, Selector};
+
+static FORM_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse("form[action]").expect("invalid FORM_SELECTOR"));
+static CHECKBOX_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("input[type=\"checkbox\"][name]").expect("invalid CHECKBOX_SELECTOR")
+});
+static LABEL_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse("label[for]").expect("invalid LABEL_SELECTOR"));
+static HIDDEN_INPUT_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("input[type=\"hidden\"][name]").expect("invalid HIDDEN_INPUT_SELECTOR")
+});
+
+/// Error that may occur while parsing an [`AccountPreferencesPage`].
+#[derive(Debug, thiserror::Error)]
+pub enum FromHtmlError {
+    /// Missing csrf token
+    #[error("missing csrf token")]
+    MissingCsrfToken,
+
+    /// Missing preferences form
+    #[error("missing preferences form")]
+    MissingForm,
+}
+
+impl FromHtmlError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingCsrfToken => Some(CSRF_TOKEN_SELECTOR),
+            Self::MissingForm => Some("input[type=\"checkbox\"][name]"),
+        }
+    }
+}
+
+/// The user preferences page. Not yet checked against the live site; see [`crate::types`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountPreferencesPage {
+    /// The csrf token
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub csrf_token: String,
+
+    /// The url the form is submitted to
+    pub action: String,
+
+    /// The hidden fields of the form, like `_method`
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::util::serialize_hidden_fields")
+    )]
+    pub hidden_fields: Vec<(String, String)>,
+
+    /// Every newsletter and notification toggle
+    pub preferences: Vec<Preference>,
+}
+
+impl AccountPreferencesPage {
+    /// Parse an [`AccountPreferencesPage`] from an html string
+    pub fn parse(html: &str) -> Result<Self, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html))
+    }
+
+    /// Parse an [`AccountPreferencesPage`] from html
+    pub(crate) fn from_html(html: &Html) -> Result<Self, FromHtmlError> {
+        let csrf_token = extract_csrf_token(html)
+            .ok_or(FromHtmlError::MissingCsrfToken)?
+            .to_string();
+
+        let form = html
+            .select(&FORM_SELECTOR)
+            .find(|form| form.select(&CHECKBOX_SELECTOR).next().is_some())
+            .ok_or(FromHtmlError::MissingForm)?;
+        let action = form
+            .value()
+            .attr("action")
+            .ok_or(FromHtmlError::MissingForm)?
+            .to_string();
+
+        let hidden_fields = extract_hidden_fields(form);
+
+        let preferences = form
+            .select(&CHECKBOX_SELECTOR)
+            .map(|input| Preference::from_element(form, input))
+            .collect();
+
+        Ok(Self {
+            csrf_token,
+            action,
+            hidden_fields,
+            preferences,
+        })
+    }
+
+    /// Get a preference by its input name
+    pub fn get(&self, name: &str) -> Option<&Preference> {
+        self.preferences
+            .iter()
+            .find(|preference| preference.name == name)
+    }
+
+    /// Set a preference by its input name.
+    ///
+    /// Returns `false` if there is no such preference.
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        match self
+            .preferences
+            .iter_mut()
+            .find(|preference| preference.name == name)
+        {
+            Some(preference) => {
+                preference.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Disable every preference, opting out of all emails
+    pub fn disable_all(&mut self) {
+        for preference in self.preferences.iter_mut() {
+            preference.enabled = false;
+        }
+    }
+
+    /// Get the form fields to submit, with the given csrf token.
+    ///
+    /// Like a browser, a disabled preference sends the value of its hidden fallback input,
+    /// or nothing if it has none.
+    pub fn form_fields<'a>(&'a self, csrf_token: &'a str) -> Vec<(&'a str, &'a str)> {
+        let hidden_fields = self
+            .hidden_fields
+            .iter()
+            .map(|(name, value)| match name.as_str() {
+                "authenticity_token" => (name.as_str(), csrf_token),
+                _ => (name.as_str(), value.as_str()),
+            });
+        let preferences = self.preferences.iter().filter_map(|preference| {
+            let value = if preference.enabled {
+                preference.checked_value.as_str()
+            } else {
+                preference.unchecked_value.as_deref()?
+            };
+            Some((preference.name.as_str(), value))
+        });
+
+        hidden_fields.chain(preferences).collect()
+    }
+}
+
+/// A newsletter or notification toggle on the [`AccountPreferencesPage`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Preference {
+    /// The input name, like `user[email_preferences][newsletter]`
+    pub name: String,
+
+    /// The human-readable label
+    pub label: String,
+
+    /// Whether the toggle is on
+    pub enabled: bool,
+
+    /// The value submitted when the toggle is on
+    checked_value: String,
+
+    /// The value of the hidden input submitted when the toggle is off, if any
+    unchecked_value: Option<String>,
+}
+
+impl Preference {
+    /// Parse a [`Preference`] from a checkbox in a form
+    fn from_element(form: ElementRef, input: ElementRef) -> Self {
+        let name = input.value().attr("name").unwrap_or_default().to_string();
+        let enabled = input.value().attr("checked").is_some();
+        let checked_value = input.value().attr("value").unwrap_or("1").to_string();
+        let unchecked_value = form
+            .select(&HIDDEN_INPUT_SELECTOR)
+            .find(|hidden| hidden.value().attr("name") == Some(name.as_str()))
+            .map(|hidden| hidden.value().attr("value").unwrap_or_default().to_string());
+
+        let label = input
+            .value()
+            .id()
+            .and_then(|id| {
+                form.select(&LABEL_SELECTOR)
+                    .find(|label| label.value().attr("for") == Some(id))
+            })
+            .map(|label| label.text().collect::<String>().trim().to_string())
+            .unwrap_or_else(|| name.clone());
+
+        Self {
+            name,
+            label,
+            enabled,
+            checked_value,
+            unchecked_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_1: &str = include_str!("../../test_data/account_preferences.html");
+
+    #[test]
+    fn sample_1() {
+        let html = Html::parse_document(SAMPLE_1);
+        let mut page =
+            AccountPreferencesPage::from_html(&html).expect("invalid account preferences page");
+
+        assert_eq!(page.action, "/account/preferences");
+        assert_eq!(page.preferences.len(), 4);
+
+        let newsletter = page
+            .get("user[email_preferences][newsletter]")
+            .expect("missing newsletter preference");
+        assert_eq!(newsletter.label, "Gearbox Newsletter");
+        assert!(newsletter.enabled);
+        assert!(
+            !page
+                .get("user[email_preferences][shift_codes]")
+                .unwrap()
+                .enabled
+        );
+
+        // The hidden "0" inputs belong to the checkboxes, not the form.
+        assert!(!page
+            .hidden_fields
+            .iter()
+            .any(|(name, _)| name.starts_with("user[email_preferences]")));
+
+        page.disable_all();
+        let fields = page.form_fields("token");
+        assert!(fields.contains(&("authenticity_token", "token")));
+        assert!(fields.contains(&("user[email_preferences][newsletter]", "0")));
+        assert!(fields.contains(&("user[email_preferences][promotions]", "false")));
+        assert!(!fields
+            .iter()
+            .any(|(name, _)| *name == "user[email_preferences][account_notifications]"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    types::Service,
+    util::{extract_csrf_token, CSRF_TOKEN_SELECTOR},
+};
+use once_cell::sync::Lazy;
+use scraper::{ElementRef, Html, Selector};
+
+static ASSOCIATION_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse(".sh_association[data-service]").expect("invalid ASSOCIATION_SELECTOR")
+});
+static ASSOCIATION_NAME_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse(".sh_association_name").expect("invalid ASSOCIATION_NAME_SELECTOR")
+});
+static ASSOCIATION_DISPLAY_NAME_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse(".sh_association_display_name")
+        .expect("invalid ASSOCIATION_DISPLAY_NAME_SELECTOR")
+});
+static UNLINK_BUTTON_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse(".sh_unlink_button").expect("invalid UNLINK_BUTTON_SELECTOR"));
+
+/// Error that may occur while parsing an [`AssociationsPage`].
+#[derive(Debug, thiserror::Error)]
+pub enum FromHtmlError {
+    /// Missing csrf token
+    #[error("missing csrf token")]
+    MissingCsrfToken,
+
+    /// Missing platform name
+    #[error("missing platform name for service `{0}`")]
+    MissingName(String),
+}
+
+impl FromHtmlError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingCsrfToken => Some(CSRF_TOKEN_SELECTOR),
+            Self::MissingName(_) => Some(".sh_association_name"),
+        }
+    }
+}
+
+/// The gaming platforms page. Not yet checked against the live site; see [`crate::types`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssociationsPage {
+    /// The csrf token
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub csrf_token: String,
+
+    /// Every platform listed, linked or not
+    pub associations: Vec<Association>,
+}
+
+impl AssociationsPage {
+    /// Parse an [`AssociationsPage`] from an html string
+    pub fn parse(html: &str) -> Result<Self, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html))
+    }
+
+    /// Parse an [`AssociationsPage`] from html
+    pub(crate) fn from_html(html: &Html) -> Result<Self, FromHtmlError> {
+        let csrf_token = extract_csrf_token(html)
+            .ok_or(FromHtmlError::MissingCsrfToken)?
+            .to_string();
+
+        let associations = html
+            .select(&ASSOCIATION_SELECTOR)
+            .map(Association::from_element)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            csrf_token,
+            associations,
+        })
+    }
+
+    /// Iterate over the linked platforms
+    pub fn linked(&self) -> impl Iterator<Item = &Association> {
+        self.associations
+            .iter()
+            .filter(|association| association.is_linked())
+    }
+
+    /// Check whether the platform for the given service is linked
+    pub fn is_linked(&self, service: &Service) -> bool {
+        self.linked()
+            .any(|association| association.service == *service)
+    }
+}
+
+/// A gaming platform on the [`AssociationsPage`]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Association {
+    /// The service
+    pub service: Service,
+
+    /// The human-readable platform name, like "Steam"
+    pub name: String,
+
+    /// The display name of the linked platform account.
+    ///
+    /// This is `None` if the platform is not linked.
+    pub display_name: Option<String>,
+}
+
+impl Association {
+    /// Parse an [`Association`] from an element
+    fn from_element(element: ElementRef) -> Result<Self, FromHtmlError> {
+        let service = Service::from(element.value().attr("data-service").unwrap_or_default());
+
+        let name = element
+            .select(&ASSOCIATION_NAME_SELECTOR)
+            .next()
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .ok_or_else(|| FromHtmlError::MissingName(service.to_string()))?;
+
+        let is_linked = element.select(&UNLINK_BUTTON_SELECTOR).next().is_some();
+        let display_name = element
+            .select(&ASSOCIATION_DISPLAY_NAME_SELECTOR)
+            .next()
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .filter(|display_name| is_linked && !display_name.is_empty());
+
+        Ok(Self {
+            service,
+            name,
+            display_name,
+        })
+    }
+
+    /// Check whether this platform is linked
+    pub fn is_linked(&self) -> bool {
+        self.display_name.is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_1: &str = include_str!("../../test_data/associations.html");
+
+    #[test]
+    fn sample_1() {
+        let html = Html::parse_document(SAMPLE_1);
+        let page = AssociationsPage::from_html(&html).expect("invalid associations page");
+
+        assert_eq!(page.associations.len(), 4);
+        assert!(page.is_linked(&Service::Steam));
+        assert!(page.is_linked(&Service::XboxLive));
+        assert!(!page.is_linked(&Service::Epic));
+        assert!(!page.is_linked(&Service::Psn));
+
+        let steam = &page.associations[0];
+        assert_eq!(steam.name, "Steam");
+        assert_eq!(steam.display_name.as_deref(), Some("adumbidiot"));
+    }
+}
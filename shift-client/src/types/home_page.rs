@@ -1,4 +1,4 @@
-use crate::util::extract_csrf_token;
+use crate::util::{extract_csrf_token, CSRF_TOKEN_SELECTOR};
 use scraper::Html;
 
 /// Error that may occur while parsing a [`HomePage`].
@@ -9,14 +9,30 @@ pub enum FromHtmlError {
     MissingCsrfToken,
 }
 
+impl FromHtmlError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingCsrfToken => Some(CSRF_TOKEN_SELECTOR),
+        }
+    }
+}
+
 /// The home page
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HomePage {
     /// The csrf token
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub csrf_token: String,
 }
 
 impl HomePage {
+    /// Parse a [`HomePage`] from an html string
+    pub fn parse(html: &str) -> Result<Self, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html))
+    }
+
     /// Parse a [`HomePage`] from html
     pub(crate) fn from_html(html: &Html) -> Result<Self, FromHtmlError> {
         let csrf_token = extract_csrf_token(html)
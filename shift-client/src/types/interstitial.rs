@@ -0,0 +1,118 @@
+use once_cell::sync::Lazy;
+use scraper::{Html, Selector};
+use url::Url;
+
+static TITLE_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse("title").expect("invalid TITLE_SELECTOR"));
+static CAPTCHA_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse(
+        ".g-recaptcha, .h-captcha, .cf-turnstile, #challenge-form, #cf-challenge-running, \
+         iframe[src*=\"recaptcha\"], iframe[src*=\"hcaptcha\"], \
+         iframe[src*=\"challenges.cloudflare.com\"]",
+    )
+    .expect("invalid CAPTCHA_SELECTOR")
+});
+
+const CAPTCHA_TITLES: &[&str] = &[
+    "just a moment...",
+    "attention required",
+    "verify you are human",
+];
+const EMAIL_VERIFICATION_TITLES: &[&str] = &["verify your email", "confirm your email"];
+const ACCOUNT_LOCKED_TITLES: &[&str] = &["account locked", "account suspended"];
+
+/// A page served instead of the one that was requested.
+/// The markers are not yet checked against the live site; see [`crate::types`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Interstitial {
+    /// The site is down for maintenance
+    Maintenance,
+
+    /// A captcha or bot challenge must be solved
+    Captcha,
+
+    /// The account email must be verified
+    EmailVerification,
+
+    /// The account is locked
+    AccountLocked,
+}
+
+impl Interstitial {
+    /// Classify an html string served at the given url with the given HTTP status,
+    /// returning `None` if it is not an interstitial.
+    pub fn parse(html: &str, url: &Url, status: u16) -> Option<Self> {
+        Self::detect(&Html::parse_document(html), url, status)
+    }
+
+    /// Classify a page, returning `None` if it is not an interstitial.
+    ///
+    /// This only looks at the path, the status, the title and challenge widgets, never the body text,
+    /// so a normal page that mentions maintenance or a locked account is not mistaken for anything.
+    /// The client only calls this once the expected page failed to parse or an error status was returned.
+    pub(crate) fn detect(html: &Html, url: &Url, status: u16) -> Option<Self> {
+        let path = url.path();
+        let title = html
+            .select(&TITLE_SELECTOR)
+            .next()
+            .map(|title| title.text().collect::<String>().to_lowercase())
+            .unwrap_or_default();
+        let title_contains_any = |titles: &[&str]| titles.iter().any(|text| title.contains(text));
+
+        if html.select(&CAPTCHA_SELECTOR).next().is_some() || title_contains_any(CAPTCHA_TITLES) {
+            return Some(Self::Captcha);
+        }
+        if path.starts_with("/maintenance") || title.contains("maintenance") || status == 503 {
+            return Some(Self::Maintenance);
+        }
+        if path.contains("/unlock") || title_contains_any(ACCOUNT_LOCKED_TITLES) {
+            return Some(Self::AccountLocked);
+        }
+        if path.contains("/confirmation") || title_contains_any(EMAIL_VERIFICATION_TITLES) {
+            return Some(Self::EmailVerification);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::RewardsPage;
+
+    const REWARDS_PAGE: &str = include_str!("../../test_data/rewards_page_1.html");
+    const MAINTENANCE: &str = include_str!("../../test_data/maintenance.html");
+
+    #[test]
+    fn rewards_page_is_not_interstitial() {
+        let html = Html::parse_document(REWARDS_PAGE);
+        let url = Url::parse("https://shift.gearboxsoftware.com/rewards").unwrap();
+        assert_eq!(Interstitial::detect(&html, &url, 200), None);
+    }
+
+    #[test]
+    fn rewards_page_mentioning_interstitials() {
+        let text = REWARDS_PAGE.replace(
+            "<body class=\"sh_signed_in\">",
+            "<body class=\"sh_signed_in\"><p>SHiFT will be down for scheduled maintenance on Tuesday. \
+             Verify your email and check your browser if your account is locked.</p>",
+        );
+        let html = Html::parse_document(&text);
+        let url = Url::parse("https://shift.gearboxsoftware.com/rewards").unwrap();
+        assert_eq!(Interstitial::detect(&html, &url, 200), None);
+        RewardsPage::from_html(&html).expect("invalid rewards page");
+    }
+
+    #[test]
+    fn maintenance() {
+        let html = Html::parse_document(MAINTENANCE);
+        let url = Url::parse("https://shift.gearboxsoftware.com/home").unwrap();
+        assert_eq!(
+            Interstitial::detect(&html, &url, 503),
+            Some(Interstitial::Maintenance)
+        );
+    }
+}
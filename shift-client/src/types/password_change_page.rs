@@ -0,0 +1,145 @@
+use crate::util::{extract_csrf_token, extract_hidden_fields, CSRF_TOKEN_SELECTOR};
+use once_cell::sync::Lazy;
+use scraper::{Html, Selector};
+
+static FORM_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse("form[action]").expect("invalid FORM_SELECTOR"));
+static PASSWORD_INPUT_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("input[type=\"password\"][name]").expect("invalid PASSWORD_INPUT_SELECTOR")
+});
+
+/// Error that may occur while parsing a [`PasswordChangePage`].
+#[derive(Debug, thiserror::Error)]
+pub enum FromHtmlError {
+    /// Missing csrf token
+    #[error("missing csrf token")]
+    MissingCsrfToken,
+
+    /// Missing password form
+    #[error("missing password form")]
+    MissingForm,
+
+    /// Missing current password input
+    #[error("missing current password input")]
+    MissingCurrentPasswordInput,
+
+    /// Missing new password input
+    #[error("missing new password input")]
+    MissingNewPasswordInput,
+}
+
+impl FromHtmlError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingCsrfToken => Some(CSRF_TOKEN_SELECTOR),
+            Self::MissingForm => Some("form[action]"),
+            Self::MissingCurrentPasswordInput | Self::MissingNewPasswordInput => {
+                Some("input[type=\"password\"][name]")
+            }
+        }
+    }
+}
+
+/// The password change page. Not yet checked against the live site; see [`crate::types`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PasswordChangePage {
+    /// The csrf token
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub csrf_token: String,
+
+    /// The url the form is submitted to
+    pub action: String,
+
+    /// The hidden fields of the form, like `_method`
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::util::serialize_hidden_fields")
+    )]
+    pub hidden_fields: Vec<(String, String)>,
+
+    /// The name of the current password input
+    pub current_password_name: String,
+
+    /// The name of the new password input
+    pub new_password_name: String,
+
+    /// The name of the new password confirmation input, if any
+    pub password_confirmation_name: Option<String>,
+}
+
+impl PasswordChangePage {
+    /// Parse a [`PasswordChangePage`] from an html string
+    pub fn parse(html: &str) -> Result<Self, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html))
+    }
+
+    /// Parse a [`PasswordChangePage`] from html
+    pub(crate) fn from_html(html: &Html) -> Result<Self, FromHtmlError> {
+        let csrf_token = extract_csrf_token(html)
+            .ok_or(FromHtmlError::MissingCsrfToken)?
+            .to_string();
+
+        let form = html
+            .select(&FORM_SELECTOR)
+            .find(|form| form.select(&PASSWORD_INPUT_SELECTOR).next().is_some())
+            .ok_or(FromHtmlError::MissingForm)?;
+        let action = form
+            .value()
+            .attr("action")
+            .ok_or(FromHtmlError::MissingForm)?
+            .to_string();
+
+        let hidden_fields = extract_hidden_fields(form);
+
+        let mut current_password_name = None;
+        let mut new_password_name = None;
+        let mut password_confirmation_name = None;
+        for input in form.select(&PASSWORD_INPUT_SELECTOR) {
+            let name = input.value().attr("name").unwrap_or_default().to_string();
+            if name.contains("current") {
+                current_password_name = Some(name);
+            } else if name.contains("confirmation") {
+                password_confirmation_name = Some(name);
+            } else {
+                new_password_name = Some(name);
+            }
+        }
+
+        Ok(Self {
+            csrf_token,
+            action,
+            hidden_fields,
+            current_password_name: current_password_name
+                .ok_or(FromHtmlError::MissingCurrentPasswordInput)?,
+            new_password_name: new_password_name.ok_or(FromHtmlError::MissingNewPasswordInput)?,
+            password_confirmation_name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_1: &str = include_str!("../../test_data/password_change.html");
+
+    #[test]
+    fn sample_1() {
+        let html = Html::parse_document(SAMPLE_1);
+        let page = PasswordChangePage::from_html(&html).expect("invalid password change page");
+
+        assert_eq!(page.action, "/password");
+        assert_eq!(page.current_password_name, "user[current_password]");
+        assert_eq!(page.new_password_name, "user[password]");
+        assert_eq!(
+            page.password_confirmation_name.as_deref(),
+            Some("user[password_confirmation]")
+        );
+        assert!(page
+            .hidden_fields
+            .iter()
+            .any(|(name, value)| name == "_method" && value == "put"));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::types::{
+    rewards::{CodeRedemptionJson, RedemptionStatus},
+    Service, ShiftCode, Title,
+};
+
+/// The result of redeeming a code with [`Client::redeem_code`](crate::Client::redeem_code)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedemptionReport {
+    /// The code that was redeemed
+    pub code: ShiftCode,
+
+    /// Set if the code was rejected before any service was offered, like if it expired
+    pub rejection: Option<RedemptionOutcome>,
+
+    /// The outcome for every offered service and title
+    pub entries: Vec<RedemptionEntry>,
+}
+
+impl RedemptionReport {
+    /// Check whether the code was redeemed for at least one service
+    pub fn is_redeemed(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches!(entry.outcome, RedemptionOutcome::Redeemed))
+    }
+}
+
+/// The outcome of redeeming a code for a single service and title
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedemptionEntry {
+    /// The service
+    pub service: Service,
+
+    /// The title
+    pub title: Title,
+
+    /// The outcome
+    pub outcome: RedemptionOutcome,
+}
+
+/// The outcome of a redemption
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RedemptionOutcome {
+    /// The code was redeemed
+    Redeemed,
+
+    /// The code was already redeemed
+    AlreadyRedeemed,
+
+    /// The code has expired
+    Expired,
+
+    /// The code does not exist
+    NonExistent,
+
+    /// The code is not available for this account
+    NotAvailable,
+
+    /// A SHiFT-enabled game must be launched before redeeming more codes
+    LaunchGame,
+
+    /// The redemption failed
+    Failed,
+
+    /// The server responded with text that is not recognized
+    Unknown(String),
+
+    /// Redeeming this service and title failed with an error, like a server error or an expired session.
+    ///
+    /// The other services and titles are still tried.
+    Error(String),
+}
+
+impl RedemptionOutcome {
+    /// Check whether trying the code again cannot change this outcome.
+    ///
+    /// Redeemed, already redeemed, expired and non-existent codes are final.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Redeemed | Self::AlreadyRedeemed | Self::Expired | Self::NonExistent
+        )
+    }
+
+    /// Get the outcome of a finished [`CodeRedemptionJson`]
+    pub fn from_json(json: &CodeRedemptionJson) -> Self {
+        match json.status() {
+            RedemptionStatus::Success => Self::Redeemed,
+            RedemptionStatus::Failed => Self::Failed,
+            RedemptionStatus::AlreadyRedeemed => Self::AlreadyRedeemed,
+            RedemptionStatus::LaunchGameRequired => Self::LaunchGame,
+            RedemptionStatus::InProgress => Self::Unknown(json.text.clone().unwrap_or_default()),
+            RedemptionStatus::Unrecognized(text) => Self::Unknown(text),
+        }
+    }
+}
@@ -1,11 +1,13 @@
 pub mod code_redemption_json;
 pub mod code_redemption_page;
+pub mod redemption_message;
 pub mod reward_form;
 pub mod rewards_page;
 
 pub use self::{
-    code_redemption_json::CodeRedemptionJson,
+    code_redemption_json::{CodeRedemptionJson, FollowUpPage, RedemptionStatus},
     code_redemption_page::CodeRedemptionPage,
+    redemption_message::RedemptionMessage,
     reward_form::RewardForm,
-    rewards_page::{AlertNotice, RewardsPage},
+    rewards_page::{AlertKind, AlertNotice, RewardHistoryEntry, RewardsPage},
 };
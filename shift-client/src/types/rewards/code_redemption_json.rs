@@ -1,6 +1,8 @@
+use super::RedemptionMessage;
 use std::collections::HashMap;
 
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CodeRedemptionJson {
     pub in_progress: Option<bool>,
     pub text: Option<String>,
@@ -16,11 +18,86 @@ impl CodeRedemptionJson {
     }
 
     pub fn is_success(&self) -> bool {
-        match self.text.as_deref() {
-            Some("Failed to redeem your SHiFT code") => false,
-            Some("Your code was successfully redeemed") => true,
-            Some(_s) => false,
-            None => false,
+        matches!(self.status(), RedemptionStatus::Success)
+    }
+
+    /// Get the status of the redemption
+    pub fn status(&self) -> RedemptionStatus {
+        if self.in_progress() {
+            return RedemptionStatus::InProgress;
+        }
+
+        let text = self.text.as_deref().unwrap_or_default();
+        match RedemptionMessage::from_text(text) {
+            Some(RedemptionMessage::Redeemed) => RedemptionStatus::Success,
+            Some(RedemptionMessage::RedeemFailed) => RedemptionStatus::Failed,
+            Some(RedemptionMessage::AlreadyRedeemed) => RedemptionStatus::AlreadyRedeemed,
+            Some(RedemptionMessage::LaunchShiftGame) => RedemptionStatus::LaunchGameRequired,
+            None => RedemptionStatus::Unrecognized(text.to_string()),
+        }
+    }
+
+    /// Get the page the `url` field points at
+    pub fn follow_up(&self) -> Option<FollowUpPage> {
+        self.url.as_deref().map(FollowUpPage::from_url)
+    }
+}
+
+/// The status of a code redemption
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RedemptionStatus {
+    /// The code was redeemed
+    Success,
+
+    /// The redemption failed
+    Failed,
+
+    /// The code was already redeemed
+    AlreadyRedeemed,
+
+    /// A SHiFT-enabled game must be launched before redeeming more codes
+    LaunchGameRequired,
+
+    /// The redemption is still in progress
+    InProgress,
+
+    /// The text was not recognized
+    Unrecognized(String),
+}
+
+/// The page a [`CodeRedemptionJson`] points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FollowUpPage {
+    /// The rewards page
+    Rewards,
+
+    /// A code redemption page, with the redemption id
+    CodeRedemption(String),
+
+    /// Some other page, with the url as given
+    Other(String),
+}
+
+impl FollowUpPage {
+    /// Classify a url, which is usually only a path
+    fn from_url(url: &str) -> Self {
+        let absolute = url::Url::parse(url).ok();
+        let path = match absolute.as_ref() {
+            Some(absolute) => absolute.path(),
+            None => url.split(['?', '#']).next().unwrap_or_default(),
+        };
+
+        if path == "/rewards" {
+            return Self::Rewards;
+        }
+
+        match path.strip_prefix("/code_redemptions/") {
+            Some(id) if !id.is_empty() && !id.contains('/') => Self::CodeRedemption(id.to_string()),
+            _ => Self::Other(url.to_string()),
         }
     }
 }
@@ -34,11 +111,23 @@ mod test {
 
     #[test]
     fn sample_1() {
-        let _json: CodeRedemptionJson = serde_json::from_str(SAMPLE_1).unwrap();
+        let json: CodeRedemptionJson = serde_json::from_str(SAMPLE_1).unwrap();
+        assert_eq!(json.status(), RedemptionStatus::InProgress);
+        assert_eq!(json.follow_up(), None);
     }
 
     #[test]
     fn sample_2() {
-        let _json: CodeRedemptionJson = serde_json::from_str(SAMPLE_2).unwrap();
+        let json: CodeRedemptionJson = serde_json::from_str(SAMPLE_2).unwrap();
+        assert_eq!(json.status(), RedemptionStatus::Failed);
+        assert_eq!(json.follow_up(), Some(FollowUpPage::Rewards));
+    }
+
+    #[test]
+    fn follow_up_code_redemption() {
+        assert_eq!(
+            FollowUpPage::from_url("/code_redemptions/2667cef2-2d2b-4631-b62b-cb9bbebbf2d4"),
+            FollowUpPage::CodeRedemption("2667cef2-2d2b-4631-b62b-cb9bbebbf2d4".into())
+        );
     }
 }
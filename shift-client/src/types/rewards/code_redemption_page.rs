@@ -1,5 +1,6 @@
-use crate::util::extract_csrf_token;
+use crate::util::{extract_csrf_token, CSRF_TOKEN_SELECTOR};
 use scraper::{Html, Selector};
+use url::Url;
 
 /// Error that may occur while parsing a [`CodeRedemptionPage`]
 #[derive(Debug, thiserror::Error)]
@@ -13,18 +14,36 @@ pub enum FromHtmlError {
     MissingCheckRedemptionStatusUrl,
 }
 
+impl FromHtmlError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingCsrfToken => Some(CSRF_TOKEN_SELECTOR),
+            Self::MissingCheckRedemptionStatusUrl => Some("#check_redemption_status[data-url]"),
+        }
+    }
+}
+
 /// A code redemption page
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeRedemptionPage {
     /// The csrf token
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub csrf_token: String,
 
-    /// The check_redemption_status_url
+    /// The check_redemption_status_url, resolved against the base url
     pub check_redemption_status_url: String,
 }
 
 impl CodeRedemptionPage {
-    pub(crate) fn from_html(html: &Html) -> Result<Self, FromHtmlError> {
+    /// Parse a [`CodeRedemptionPage`] from an html string, resolving urls against the given base url
+    pub fn parse(html: &str, base_url: &Url) -> Result<Self, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html), base_url)
+    }
+
+    /// Parse a [`CodeRedemptionPage`] from html, resolving urls against the given base url
+    pub(crate) fn from_html(html: &Html, base_url: &Url) -> Result<Self, FromHtmlError> {
         let csrf_token = extract_csrf_token(html)
             .ok_or(FromHtmlError::MissingCsrfToken)?
             .to_string();
@@ -36,7 +55,8 @@ impl CodeRedemptionPage {
             .select(&check_redemption_status_url_selector)
             .next()
             .and_then(|element| element.value().attr("data-url"))
-            .map(|url| format!("https://shift.gearboxsoftware.com{url}"))
+            .and_then(|url| base_url.join(url).ok())
+            .map(String::from)
             .ok_or(FromHtmlError::MissingCheckRedemptionStatusUrl)?;
 
         Ok(Self {
@@ -54,7 +74,12 @@ mod test {
 
     #[test]
     fn sample_1() {
-        let html = Html::parse_document(SAMPLE_1);
-        let _page = CodeRedemptionPage::from_html(&html).expect("invalid code redemption page");
+        let base_url = Url::parse("https://shift.gearboxsoftware.com/").unwrap();
+        let page =
+            CodeRedemptionPage::parse(SAMPLE_1, &base_url).expect("invalid code redemption page");
+        assert_eq!(
+            page.check_redemption_status_url,
+            "https://shift.gearboxsoftware.com/code_redemptions/2667cef2-2d2b-4631-b62b-cb9bbebbf2d4"
+        );
     }
 }
@@ -0,0 +1,34 @@
+/// A known message the SHiFT site shows about a code redemption.
+///
+/// These show up both as [`AlertNotice`](super::AlertNotice)s and as the text of a [`CodeRedemptionJson`](super::CodeRedemptionJson).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RedemptionMessage {
+    /// The code was redeemed
+    Redeemed,
+
+    /// The redemption failed
+    RedeemFailed,
+
+    /// The code was already redeemed
+    AlreadyRedeemed,
+
+    /// A SHiFT-enabled game must be launched before redeeming more codes
+    LaunchShiftGame,
+}
+
+impl RedemptionMessage {
+    /// Recognize a message from its text
+    pub fn from_text(text: &str) -> Option<Self> {
+        match text.trim() {
+            "Your code was successfully redeemed" => Some(Self::Redeemed),
+            "Failed to redeem your SHiFT code" => Some(Self::RedeemFailed),
+            "This SHiFT code has already been redeemed" => Some(Self::AlreadyRedeemed),
+            "To continue to redeem SHiFT codes, please launch a SHiFT-enabled title first!" => {
+                Some(Self::LaunchShiftGame)
+            }
+            _ => None,
+        }
+    }
+}
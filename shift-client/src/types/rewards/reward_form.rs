@@ -1,4 +1,7 @@
-use crate::util::extract_by_name;
+use crate::{
+    types::{Service, Title},
+    util::extract_by_name,
+};
 use scraper::{ElementRef, Html, Selector};
 
 /// Error that may occur while parsing a [`RewardForm`].
@@ -30,28 +33,74 @@ pub enum FromElementError {
     MissingCommit,
 }
 
+impl FromElementError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        let selector = match self {
+            Self::MissingUtf8 => "[name=\"utf8\"][value]",
+            Self::MissingAuthToken => "[name=\"authenticity_token\"][value]",
+            Self::MissingCode => "[name=\"archway_code_redemption[code]\"][value]",
+            Self::MissingCheck => "[name=\"archway_code_redemption[check]\"][value]",
+            Self::MissingService => "[name=\"archway_code_redemption[service]\"][value]",
+            Self::MissingTitle => "[name=\"archway_code_redemption[title]\"][value]",
+            Self::MissingCommit => "[name=\"commit\"][value]",
+        };
+        Some(selector)
+    }
+}
+
 /// The reward form
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
 pub struct RewardForm {
     utf8: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
     authenticity_token: String,
 
-    #[serde(rename = "archway_code_redemption[code]")]
+    #[cfg_attr(feature = "serde", serde(rename = "code"))]
     archway_code_redemption_code: String,
 
-    #[serde(rename = "archway_code_redemption[check]")]
+    #[cfg_attr(feature = "serde", serde(rename = "check"))]
     archway_code_redemption_check: String,
 
-    #[serde(rename = "archway_code_redemption[service]")]
+    #[cfg_attr(feature = "serde", serde(rename = "service"))]
     archway_code_redemption_service: String,
 
-    #[serde(rename = "archway_code_redemption[title]")]
+    #[cfg_attr(feature = "serde", serde(rename = "title"))]
     archway_code_redemption_title: String,
 
     commit: String,
 }
 
+/// The fields of a [`RewardForm`] as they are posted
+#[cfg(feature = "client")]
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct RewardFormPost<'a> {
+    utf8: &'a str,
+    authenticity_token: &'a str,
+
+    #[serde(rename = "archway_code_redemption[code]")]
+    archway_code_redemption_code: &'a str,
+
+    #[serde(rename = "archway_code_redemption[check]")]
+    archway_code_redemption_check: &'a str,
+
+    #[serde(rename = "archway_code_redemption[service]")]
+    archway_code_redemption_service: &'a str,
+
+    #[serde(rename = "archway_code_redemption[title]")]
+    archway_code_redemption_title: &'a str,
+
+    commit: &'a str,
+}
+
 impl RewardForm {
+    /// Parse every [`RewardForm`] from an html string, like the response of the entitlement offer codes endpoint
+    pub fn parse(html: &str) -> Result<Vec<Self>, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html))
+    }
+
     /// Parse a [`RewardForm`] from html
     pub(crate) fn from_html(html: &Html) -> Result<Vec<Self>, FromHtmlError> {
         let form_selector = Selector::parse("form").expect("invalid form selector");
@@ -104,6 +153,46 @@ impl RewardForm {
             commit,
         })
     }
+
+    /// The code this form redeems
+    pub fn code(&self) -> &str {
+        &self.archway_code_redemption_code
+    }
+
+    /// The service this form redeems the code on
+    pub fn service(&self) -> Service {
+        Service::from(self.archway_code_redemption_service.as_str())
+    }
+
+    /// The title this form redeems the code for
+    pub fn title(&self) -> Title {
+        Title::from(self.archway_code_redemption_title.as_str())
+    }
+
+    /// The label of the submit button, like "Redeem for Steam"
+    pub fn commit(&self) -> &str {
+        &self.commit
+    }
+
+    /// The authenticity token submitted with this form
+    #[cfg(feature = "client")]
+    pub(crate) fn authenticity_token(&self) -> &str {
+        &self.authenticity_token
+    }
+
+    /// Get the fields to post for this form, with the given authenticity token
+    #[cfg(feature = "client")]
+    pub(crate) fn to_post<'a>(&'a self, authenticity_token: &'a str) -> RewardFormPost<'a> {
+        RewardFormPost {
+            utf8: &self.utf8,
+            authenticity_token,
+            archway_code_redemption_code: &self.archway_code_redemption_code,
+            archway_code_redemption_check: &self.archway_code_redemption_check,
+            archway_code_redemption_service: &self.archway_code_redemption_service,
+            archway_code_redemption_title: &self.archway_code_redemption_title,
+            commit: &self.commit,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -114,6 +203,10 @@ mod test {
     #[test]
     fn sampe_1() {
         let doc = Html::parse_document(SAMPLE_1);
-        let _form = RewardForm::from_html(&doc).expect("Failed to parse reward form");
+        let forms = RewardForm::from_html(&doc).expect("Failed to parse reward form");
+        assert_eq!(forms.len(), 1);
+        assert_eq!(forms[0].code(), "KJKBT-KCBJC-FXT65-TTTT3-W3XSR");
+        assert_eq!(forms[0].service(), Service::Steam);
+        assert_eq!(forms[0].title(), Title::Borderlands2);
     }
 }
@@ -1,9 +1,27 @@
-use crate::util::extract_csrf_token;
+use super::RedemptionMessage;
+use crate::util::{extract_csrf_token, CSRF_TOKEN_SELECTOR};
 use once_cell::sync::Lazy;
-use scraper::{ElementRef, Html, Selector};
+use scraper::{ElementRef, Html, Node, Selector};
+use time::Date;
 
 static ALERT_NOTICE_SELECTOR: Lazy<Selector> =
-    Lazy::new(|| Selector::parse(".alert.notice p").expect("invalid ALERT_NOTICE_SELECTOR"));
+    Lazy::new(|| Selector::parse(".alert").expect("invalid ALERT_NOTICE_SELECTOR"));
+static REWARD_PLATFORM_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse(".tab-content .tab-pane").expect("invalid REWARD_PLATFORM_SELECTOR")
+});
+static REWARD_SERVICE_HEADER_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse(".reward-service-header").expect("invalid REWARD_SERVICE_HEADER_SELECTOR")
+});
+static REWARD_LIST_ITEM_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse(".sh_reward_list > .shift-secondary-title, .sh_reward_list > dl")
+        .expect("invalid REWARD_LIST_ITEM_SELECTOR")
+});
+static DT_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse("dt").expect("invalid DT_SELECTOR"));
+static DD_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse("dd").expect("invalid DD_SELECTOR"));
+static REWARD_UNLOCKED_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse(".reward_unlocked").expect("invalid REWARD_UNLOCKED_SELECTOR"));
 
 /// Error that may occur while parsing a [`RewardsPage`].
 #[derive(Debug, thiserror::Error)]
@@ -11,23 +29,40 @@ pub enum FromHtmlError {
     /// MissingCsrfToken
     #[error("missing csrf token")]
     MissingCsrfToken,
+}
 
-    /// Invalid alert notice
-    #[error("invalid alert notice")]
-    InvalidAlertNotice(#[from] FromElementError),
+impl FromHtmlError {
+    /// The selector that did not match, if any
+    pub fn selector(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingCsrfToken => Some(CSRF_TOKEN_SELECTOR),
+        }
+    }
 }
 
 /// The rewards page
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RewardsPage {
     /// The csrf token
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub csrf_token: String,
 
     /// An alert notice
     pub alert_notice: Option<AlertNotice>,
+
+    /// Every unlocked reward, in page order.
+    ///
+    /// Entries without a game or title are skipped, so an odd entry never breaks the page.
+    pub reward_history: Vec<RewardHistoryEntry>,
 }
 
 impl RewardsPage {
+    /// Parse a [`RewardsPage`] from an html string
+    pub fn parse(html: &str) -> Result<Self, FromHtmlError> {
+        Self::from_html(&Html::parse_document(html))
+    }
+
     /// Parse a [`RewardsPage`] from html
     pub(crate) fn from_html(html: &Html) -> Result<Self, FromHtmlError> {
         let csrf_token = extract_csrf_token(html)
@@ -36,29 +71,150 @@ impl RewardsPage {
 
         let alert_notice = html
             .select(&ALERT_NOTICE_SELECTOR)
-            .next()
-            .map(AlertNotice::from_element)
-            .transpose()?;
+            .find_map(AlertNotice::from_element);
+
+        let reward_history = RewardHistoryEntry::from_html(html);
 
         Ok(Self {
             csrf_token,
             alert_notice,
+            reward_history,
         })
     }
 }
 
-/// An error that may occur while parsing an AlertNotice
-#[derive(Debug, thiserror::Error)]
-pub enum FromElementError {
-    /// Missing text
-    #[error("missing text")]
-    MissingText,
+/// An unlocked reward, from the reward history of the [`RewardsPage`]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RewardHistoryEntry {
+    /// The platform the reward was unlocked on, like "steam"
+    pub platform: String,
+
+    /// The game, like "Borderlands 2"
+    pub game: String,
+
+    /// The reward title, like "5 Golden Keys"
+    pub title: String,
 
-    #[error("unknown text '{0}'")]
-    UnknownText(String),
+    /// The date the reward was unlocked, if it is present and in the expected format
+    pub unlocked: Option<Date>,
+
+    /// The reward description
+    pub description: String,
 }
 
-#[derive(Debug)]
+impl RewardHistoryEntry {
+    /// Parse all [`RewardHistoryEntry`]s from html, skipping invalid entries
+    fn from_html(html: &Html) -> Vec<Self> {
+        let mut entries = Vec::new();
+
+        for platform_element in html.select(&REWARD_PLATFORM_SELECTOR) {
+            let platform = platform_element
+                .select(&REWARD_SERVICE_HEADER_SELECTOR)
+                .next()
+                .map(|element| element.text().collect::<String>().trim().to_string())
+                .or_else(|| platform_element.value().id().map(String::from))
+                .unwrap_or_default();
+
+            let mut game = None;
+            for element in platform_element.select(&REWARD_LIST_ITEM_SELECTOR) {
+                if element.value().name() == "dl" {
+                    let Some(game) = game.clone() else {
+                        continue;
+                    };
+                    entries.extend(Self::from_element(element, platform.clone(), game));
+                } else {
+                    game = Some(element.text().collect::<String>().trim().to_string());
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Parse a [`RewardHistoryEntry`] from a `dl` element.
+    ///
+    /// Returns `None` if the entry has no title.
+    fn from_element(element: ElementRef, platform: String, game: String) -> Option<Self> {
+        const UNLOCKED_FORMAT: &[time::format_description::FormatItem<'static>] =
+            time::macros::format_description!("[month repr:long] [day padding:none], [year]");
+
+        let title = element
+            .select(&DT_SELECTOR)
+            .next()
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .filter(|title| !title.is_empty())?;
+
+        let dd = element.select(&DD_SELECTOR).next();
+
+        let unlocked = dd
+            .and_then(|dd| dd.select(&REWARD_UNLOCKED_SELECTOR).next())
+            .and_then(|element| {
+                Date::parse(element.text().collect::<String>().trim(), UNLOCKED_FORMAT).ok()
+            });
+
+        // The description is the text directly inside the `dd`, next to the unlock date.
+        let description = dd
+            .map(|dd| {
+                dd.children()
+                    .filter_map(|node| match node.value() {
+                        Node::Text(text) => Some(text.trim()),
+                        _ => None,
+                    })
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            platform,
+            game,
+            title,
+            unlocked,
+            description,
+        })
+    }
+}
+
+/// The level of an alert, taken from its css class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AlertKind {
+    /// A notice
+    Notice,
+
+    /// An error
+    Error,
+
+    /// A warning
+    Warning,
+}
+
+impl AlertKind {
+    /// Get the kind of an alert element.
+    ///
+    /// Alerts without a recognized level are treated as notices.
+    fn from_element(el: ElementRef) -> Self {
+        let mut classes = el.value().classes();
+        if classes
+            .clone()
+            .any(|class| matches!(class, "error" | "alert-danger" | "alert-error"))
+        {
+            Self::Error
+        } else if classes.any(|class| matches!(class, "warning" | "alert-warning")) {
+            Self::Warning
+        } else {
+            Self::Notice
+        }
+    }
+}
+
+/// An alert shown at the top of the [`RewardsPage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum AlertNotice {
     /// A shift code was already redeemed
     ShiftCodeAlreadyRedeemed,
@@ -71,22 +227,42 @@ pub enum AlertNotice {
 
     /// Redeem failed
     ShiftCodeRedeemFail,
+
+    /// An alert that is not recognized
+    Other {
+        /// The text of the alert
+        text: String,
+
+        /// The level of the alert
+        kind: AlertKind,
+    },
 }
 
 impl AlertNotice {
-    /// Parse an alert notice from an element
-    fn from_element(el: ElementRef) -> Result<Self, FromElementError> {
-        let text = el.text().next().ok_or(FromElementError::MissingText)?;
-
-        match text {
-            "This SHiFT code has already been redeemed" => Ok(Self::ShiftCodeAlreadyRedeemed),
-            "To continue to redeem SHiFT codes, please launch a SHiFT-enabled title first!" => {
-                Ok(Self::LaunchShiftGame)
-            }
-            "Your code was successfully redeemed" => Ok(Self::ShiftCodeRedeemed),
-            "Failed to redeem your SHiFT code" => Ok(Self::ShiftCodeRedeemFail),
-            _ => Err(FromElementError::UnknownText(text.to_string())),
+    /// Parse an alert notice from an element.
+    ///
+    /// Returns `None` if the alert has no text.
+    fn from_element(el: ElementRef) -> Option<Self> {
+        let text = el
+            .text()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.is_empty() {
+            return None;
         }
+
+        Some(match RedemptionMessage::from_text(&text) {
+            Some(RedemptionMessage::AlreadyRedeemed) => Self::ShiftCodeAlreadyRedeemed,
+            Some(RedemptionMessage::LaunchShiftGame) => Self::LaunchShiftGame,
+            Some(RedemptionMessage::Redeemed) => Self::ShiftCodeRedeemed,
+            Some(RedemptionMessage::RedeemFailed) => Self::ShiftCodeRedeemFail,
+            None => Self::Other {
+                text,
+                kind: AlertKind::from_element(el),
+            },
+        })
     }
 }
 
@@ -99,6 +275,70 @@ mod test {
     #[test]
     fn sample_1() {
         let html = Html::parse_document(SAMPLE_1);
-        let _page = RewardsPage::from_html(&html).unwrap();
+        let page = RewardsPage::from_html(&html).unwrap();
+        assert_eq!(page.alert_notice, Some(AlertNotice::ShiftCodeRedeemFail));
+
+        let first = page.reward_history.first().expect("missing reward history");
+        assert_eq!(first.platform, "steam");
+        assert_eq!(first.game, "Borderlands 2");
+        assert_eq!(first.title, "5 Golden Keys");
+        assert_eq!(first.unlocked, Some(time::macros::date!(2020 - 04 - 09)));
+        assert!(first
+            .description
+            .starts_with("You have unlocked 5 Golden Keys!"));
+
+        assert!(page
+            .reward_history
+            .iter()
+            .any(|entry| entry.game == "Borderlands: The Pre-Sequel"));
+    }
+
+    #[test]
+    fn malformed_reward_history() {
+        const SAMPLE: &str = include_str!("../../../test_data/rewards_page_malformed_history.html");
+
+        let page = RewardsPage::parse(SAMPLE).expect("malformed history should not fail the page");
+        assert_eq!(page.alert_notice, Some(AlertNotice::ShiftCodeRedeemed));
+
+        let titles: Vec<_> = page
+            .reward_history
+            .iter()
+            .map(|entry| entry.title.as_str())
+            .collect();
+        assert_eq!(
+            titles,
+            ["3 Golden Keys", "Gold Weapon Skin", "5 Golden Keys"]
+        );
+        assert_eq!(page.reward_history[0].unlocked, None);
+        assert_eq!(page.reward_history[1].unlocked, None);
+        assert_eq!(
+            page.reward_history[2].unlocked,
+            Some(time::macros::date!(2020 - 04 - 09))
+        );
+    }
+
+    #[test]
+    fn unknown_alert() {
+        let html = Html::parse_document(
+            r#"<div class="alert error"><p>Something new went wrong</p></div>"#,
+        );
+        let element = html.select(&ALERT_NOTICE_SELECTOR).next().unwrap();
+        assert_eq!(
+            AlertNotice::from_element(element),
+            Some(AlertNotice::Other {
+                text: "Something new went wrong".into(),
+                kind: AlertKind::Error,
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let page = RewardsPage::parse(SAMPLE_1).unwrap();
+        let json = serde_json::to_value(&page).unwrap();
+        assert!(json.get("csrf_token").is_none());
+        assert_eq!(json["alert_notice"], "shift_code_redeem_fail");
+        assert_eq!(json["reward_history"][0]["unlocked"], "2020-04-09");
     }
 }
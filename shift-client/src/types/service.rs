@@ -0,0 +1,158 @@
+use std::fmt;
+
+/// A platform a code can be redeemed on, from `archway_code_redemption[service]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Service {
+    /// Steam
+    Steam,
+
+    /// The Epic Games Store
+    Epic,
+
+    /// PlayStation Network
+    Psn,
+
+    /// Xbox Live
+    XboxLive,
+
+    /// Nintendo
+    Nintendo,
+
+    /// Stadia
+    Stadia,
+
+    /// A service that is not known to this library
+    Unknown(String),
+}
+
+impl Service {
+    /// Get the service name used by the SHiFT site, like "steam"
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Steam => "steam",
+            Self::Epic => "epic",
+            Self::Psn => "psn",
+            Self::XboxLive => "xboxlive",
+            Self::Nintendo => "nintendo",
+            Self::Stadia => "stadia",
+            Self::Unknown(service) => service,
+        }
+    }
+}
+
+impl From<&str> for Service {
+    fn from(service: &str) -> Self {
+        match service {
+            "steam" => Self::Steam,
+            "epic" => Self::Epic,
+            "psn" => Self::Psn,
+            "xboxlive" => Self::XboxLive,
+            "nintendo" => Self::Nintendo,
+            "stadia" => Self::Stadia,
+            service => Self::Unknown(service.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Service {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Service {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(Self::from(value.as_ref()))
+    }
+}
+
+/// A game a code can be redeemed for, from `archway_code_redemption[title]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Title {
+    /// Borderlands GOTY Enhanced
+    Borderlands,
+
+    /// Borderlands 2
+    Borderlands2,
+
+    /// Borderlands: The Pre-Sequel
+    BorderlandsPreSequel,
+
+    /// Borderlands 3
+    Borderlands3,
+
+    /// Tiny Tina's Wonderlands
+    TinyTinasWonderlands,
+
+    /// A title that is not known to this library
+    Unknown(String),
+}
+
+impl Title {
+    /// Get the title name used by the SHiFT site, like "willow2"
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Borderlands => "mopane",
+            Self::Borderlands2 => "willow2",
+            Self::BorderlandsPreSequel => "cork",
+            Self::Borderlands3 => "oak",
+            Self::TinyTinasWonderlands => "daffodil",
+            Self::Unknown(title) => title,
+        }
+    }
+}
+
+impl From<&str> for Title {
+    fn from(title: &str) -> Self {
+        match title {
+            "mopane" => Self::Borderlands,
+            "willow2" => Self::Borderlands2,
+            "cork" => Self::BorderlandsPreSequel,
+            "oak" => Self::Borderlands3,
+            "daffodil" => Self::TinyTinasWonderlands,
+            title => Self::Unknown(title.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Title {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Title {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Title {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(Self::from(value.as_ref()))
+    }
+}
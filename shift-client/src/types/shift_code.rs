@@ -0,0 +1,162 @@
+mod extract;
+
+pub use self::extract::{extract_codes, ExtractedCode};
+use std::{fmt, str::FromStr};
+
+/// The number of groups in a [`ShiftCode`]
+const GROUP_COUNT: usize = 5;
+
+/// The number of characters in each group of a [`ShiftCode`]
+const GROUP_LEN: usize = 5;
+
+/// Error that may occur while parsing a [`ShiftCode`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseShiftCodeError {
+    /// A character that can never be part of a code
+    #[error("invalid character {character:?} at position {position}")]
+    InvalidCharacter {
+        /// The character
+        character: char,
+
+        /// The index of the character in the input, counted in chars
+        position: usize,
+    },
+
+    /// The code does not have 25 characters, not counting separators
+    #[error("expected {expected} characters, found {0}", expected = GROUP_COUNT * GROUP_LEN)]
+    InvalidLength(usize),
+}
+
+/// A SHiFT code, like `KJKBT-KCBJC-FXT65-TTTT3-W3XSR`.
+///
+/// Parsing ignores case, surrounding whitespace and where the separators are,
+/// and always normalizes to five uppercase groups of five characters joined by dashes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShiftCode(String);
+
+impl ShiftCode {
+    /// Get the normalized code
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ShiftCode {
+    type Err = ParseShiftCodeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut chars = Vec::with_capacity(GROUP_COUNT * GROUP_LEN);
+        for (position, character) in input.chars().enumerate() {
+            if is_separator(character) {
+                continue;
+            }
+            if !character.is_ascii_alphanumeric() {
+                return Err(ParseShiftCodeError::InvalidCharacter {
+                    character,
+                    position,
+                });
+            }
+            chars.push(character.to_ascii_uppercase());
+        }
+
+        if chars.len() != GROUP_COUNT * GROUP_LEN {
+            return Err(ParseShiftCodeError::InvalidLength(chars.len()));
+        }
+
+        let groups: Vec<String> = chars
+            .chunks(GROUP_LEN)
+            .map(|group| group.iter().collect())
+            .collect();
+        Ok(Self(groups.join("-")))
+    }
+}
+
+impl TryFrom<&str> for ShiftCode {
+    type Error = ParseShiftCodeError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl TryFrom<String> for ShiftCode {
+    type Error = ParseShiftCodeError;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl AsRef<str> for ShiftCode {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for ShiftCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ShiftCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ShiftCode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Check whether a character may separate the groups of a code
+pub(crate) fn is_separator(character: char) -> bool {
+    character.is_whitespace() || matches!(character, '-' | '_' | '‐' | '‑' | '–' | '—')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let expected = "KJKBT-KCBJC-FXT65-TTTT3-W3XSR";
+        for input in [
+            "KJKBT-KCBJC-FXT65-TTTT3-W3XSR",
+            " kjkbt-kcbjc-fxt65-tttt3-w3xsr\n",
+            "KJKBTKCBJCFXT65TTTT3W3XSR",
+            "KJKB-TKCBJ-CFXT65TTTT3-W3XSR",
+            "KJKBT – KCBJC – FXT65 – TTTT3 – W3XSR",
+        ] {
+            let code: ShiftCode = input.parse().expect("failed to parse code");
+            assert_eq!(code.as_str(), expected, "{input:?}");
+        }
+
+        assert_eq!(
+            "KJKBT-KCBJC-FXT65-TTTT3-W3XS".parse::<ShiftCode>(),
+            Err(ParseShiftCodeError::InvalidLength(24))
+        );
+        assert_eq!(
+            "KJKBT-KCBJC-FXT65-TTTT3-W3XSR-A".parse::<ShiftCode>(),
+            Err(ParseShiftCodeError::InvalidLength(26))
+        );
+        assert_eq!(
+            "KJKBT-KCBJC-FX*65-TTTT3-W3XSR".parse::<ShiftCode>(),
+            Err(ParseShiftCodeError::InvalidCharacter {
+                character: '*',
+                position: 14,
+            })
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use super::{is_separator, ShiftCode, GROUP_COUNT, GROUP_LEN};
+use std::ops::Range;
+
+/// The most separator characters allowed between two groups
+const MAX_SEPARATOR_RUN: usize = 3;
+
+/// The most bytes of context kept on each side of a code
+const CONTEXT_LEN: usize = 40;
+
+/// A [`ShiftCode`] found in free text by [`extract_codes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtractedCode {
+    /// The normalized code
+    pub code: ShiftCode,
+
+    /// The byte range of the code in the text, including any separators
+    pub span: Range<usize>,
+
+    /// The text around the code, on the same line
+    pub context: String,
+}
+
+/// Find every SHiFT-shaped code in free text, like a chat message, tweet or html page.
+///
+/// Groups may be separated by up to three dashes, underscores, dots or whitespace, or not at all,
+/// as long as every group is separated the same way.
+/// Codes separated by whitespace must have a digit, so five-letter words in prose are not read as a code.
+/// Groups separated by anything else must not continue past either end of the code,
+/// so a run of six groups is not read as a code.
+/// Case is ignored, and look-alike characters from copy-pasted or OCR'd text,
+/// like fullwidth forms and Cyrillic or Greek capitals, are read as their ASCII counterparts.
+/// Zero-width characters and soft hyphens are skipped.
+/// Letter and digit swaps like `O` and `0` are not guessed, since both are valid code characters.
+pub fn extract_codes(text: &str) -> Vec<ExtractedCode> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut codes = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let at_word_start = i == 0 || code_char(chars[i - 1].1).is_none();
+        if at_word_start {
+            if let Some((code, end)) = match_code(&chars, i) {
+                let start = chars[i].0;
+                let end_byte = chars.get(end).map_or(text.len(), |(index, _)| *index);
+                codes.push(ExtractedCode {
+                    code,
+                    span: start..end_byte,
+                    context: context(text, start..end_byte),
+                });
+                i = end;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    codes
+}
+
+/// Try to match a code starting at the given char index, returning it and the char index after it
+fn match_code(chars: &[(usize, char)], start: usize) -> Option<(ShiftCode, usize)> {
+    let mut normalized = String::with_capacity(GROUP_COUNT * GROUP_LEN);
+    let mut first_separator = None;
+    let mut i = start;
+
+    for group in 0..GROUP_COUNT {
+        if group > 0 {
+            let mut separator = String::new();
+            while let Some((_, c)) = chars.get(i) {
+                if is_ignorable(*c) {
+                    i += 1;
+                } else if is_separator(*c) || *c == '.' {
+                    separator.push(separator_kind(*c));
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            if separator.len() > MAX_SEPARATOR_RUN {
+                return None;
+            }
+            match &first_separator {
+                Some(first) if *first != separator => return None,
+                Some(_) => {}
+                None => first_separator = Some(separator),
+            }
+        }
+
+        let mut len = 0;
+        while len < GROUP_LEN {
+            let (_, c) = chars.get(i)?;
+            i += 1;
+            if is_ignorable(*c) {
+                continue;
+            }
+            normalized.push(code_char(*c)?);
+            len += 1;
+        }
+    }
+
+    // A code must not run into more code characters, or into another group separated the same way.
+    if chars.get(i).is_some_and(|(_, c)| code_char(*c).is_some()) {
+        return None;
+    }
+    let separator = first_separator.as_deref().unwrap_or_default();
+    let after = chars[i..].iter().map(|(_, c)| *c);
+    let before = chars[..start].iter().rev().map(|(_, c)| *c);
+    if continues_through(after, separator) || continues_through(before, separator) {
+        return None;
+    }
+
+    let whitespace_separated = separator.contains(' ');
+    if whitespace_separated && !normalized.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some((normalized.parse().ok()?, i))
+}
+
+/// Check whether text next to a code starts with the given separator run followed by a code character.
+///
+/// Whitespace never continues a code, since codes in prose are followed by more words.
+fn continues_through(text: impl Iterator<Item = char>, separator: &str) -> bool {
+    if separator.is_empty() || separator.contains(' ') {
+        return false;
+    }
+
+    let mut run = String::new();
+    for c in text {
+        if is_ignorable(c) {
+            continue;
+        }
+        if is_separator(c) || c == '.' {
+            run.push(separator_kind(c));
+            continue;
+        }
+        return run == separator && code_char(c).is_some();
+    }
+    false
+}
+
+/// Get the uppercase ASCII code character a character reads as, if any
+fn code_char(c: char) -> Option<char> {
+    let c = match c {
+        // Fullwidth forms
+        '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ' => {
+            char::from_u32(u32::from(c) - 0xFEE0).expect("invalid fullwidth char")
+        }
+        // Cyrillic look-alikes
+        'А' | 'а' => 'A',
+        'В' | 'в' => 'B',
+        'С' | 'с' => 'C',
+        'Е' | 'е' => 'E',
+        'Н' | 'н' => 'H',
+        'К' | 'к' => 'K',
+        'М' | 'м' => 'M',
+        'О' | 'о' => 'O',
+        'Р' | 'р' => 'P',
+        'Т' | 'т' => 'T',
+        'Х' | 'х' => 'X',
+        'У' | 'у' => 'Y',
+        // Greek look-alikes
+        'Α' => 'A',
+        'Β' => 'B',
+        'Ε' => 'E',
+        'Η' => 'H',
+        'Ι' => 'I',
+        'Κ' => 'K',
+        'Μ' => 'M',
+        'Ν' => 'N',
+        'Ο' => 'O',
+        'Ρ' => 'P',
+        'Τ' => 'T',
+        'Χ' => 'X',
+        'Υ' => 'Y',
+        'Ζ' => 'Z',
+        c => c,
+    };
+
+    c.is_ascii_alphanumeric().then(|| c.to_ascii_uppercase())
+}
+
+/// Get the character a separator is compared as, so every space and every dash look the same
+fn separator_kind(c: char) -> char {
+    if c.is_whitespace() {
+        ' '
+    } else if c == '_' || c == '.' {
+        c
+    } else {
+        '-'
+    }
+}
+
+/// Check whether a character is invisible and should be skipped
+fn is_ignorable(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+    )
+}
+
+/// Get the text around a span, on the same line
+fn context(text: &str, span: Range<usize>) -> String {
+    let mut start = span.start.saturating_sub(CONTEXT_LEN);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let mut end = (span.end + CONTEXT_LEN).min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let start = text[start..span.start]
+        .rfind('\n')
+        .map_or(start, |index| start + index + 1);
+    let end = text[span.end..end]
+        .find('\n')
+        .map_or(end, |index| span.end + index);
+
+    text[start..end].trim().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extract() {
+        let text = "New code for BL3!\n\
+            Redeem KJKBT-KCBJC-FXT65-TTTT3-W3XSR before Sunday, \
+            or kjkbt kcbjc fxt65 tttt3 w3xsr if you like lowercase.\n\
+            <td>ＷＳＷＴＢ–ZKS3W–ZZSZK–3BB3J–WBTRW</td>\n\
+            Not codes: KJKBT-KCBJC-FXT65-TTTT3-W3XSRX, KJKBT-KCBJC-FXT65-TTTT3, \
+            ABCDE-KJKBT-KCBJC-FXT65-TTTT3-W3XSR";
+        let codes = extract_codes(text);
+
+        let found: Vec<&str> = codes.iter().map(|code| code.code.as_str()).collect();
+        assert_eq!(
+            found,
+            [
+                "KJKBT-KCBJC-FXT65-TTTT3-W3XSR",
+                "KJKBT-KCBJC-FXT65-TTTT3-W3XSR",
+                "WSWTB-ZKS3W-ZZSZK-3BB3J-WBTRW",
+            ]
+        );
+
+        assert_eq!(
+            &text[codes[0].span.clone()],
+            "KJKBT-KCBJC-FXT65-TTTT3-W3XSR"
+        );
+        assert!(codes[0].context.starts_with("Redeem KJKBT"));
+        assert!(!codes[0].context.contains("BL3"));
+        assert!(codes[2].context.starts_with("<td>"));
+    }
+
+    #[test]
+    fn prose() {
+        let text = "hello world there again story, \
+            KJKBT-KCBJC FXT65-TTTT3-W3XSR, \
+            and ABCDE FGHIJ KLMNO PQRST UVWXY";
+        assert_eq!(extract_codes(text), []);
+    }
+
+    #[test]
+    fn contiguous() {
+        let codes = extract_codes("code:KJKBTKCBJCFXT65TTTT3W3XSR!");
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].span, 5..30);
+    }
+}
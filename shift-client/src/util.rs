@@ -1,5 +1,12 @@
 use once_cell::sync::Lazy;
 use scraper::{ElementRef, Html, Selector};
+#[cfg(feature = "client")]
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+#[cfg(feature = "client")]
+use url::Url;
 
 /// Extract the value of an element by the name attr
 pub(crate) fn extract_by_name<'a>(element: ElementRef<'a>, name: &str) -> Option<&'a str> {
@@ -7,10 +14,107 @@ pub(crate) fn extract_by_name<'a>(element: ElementRef<'a>, name: &str) -> Option
     element.select(&selector).next()?.value().attr("value")
 }
 
+/// The selector for the csrf token
+pub(crate) const CSRF_TOKEN_SELECTOR: &str = "meta[name=\"csrf-token\"][content]";
+
 /// Extract the csrf token
 pub(crate) fn extract_csrf_token(html: &Html) -> Option<&str> {
-    static META_SELECTOR: Lazy<Selector> = Lazy::new(|| {
-        Selector::parse("meta[name=\"csrf-token\"][content]").expect("invalid META_SELECTOR")
-    });
+    static META_SELECTOR: Lazy<Selector> =
+        Lazy::new(|| Selector::parse(CSRF_TOKEN_SELECTOR).expect("invalid META_SELECTOR"));
     html.select(&META_SELECTOR).next()?.value().attr("content")
 }
+
+/// Extract the hidden fields and the named submit button of a form, in order.
+///
+/// Hidden fields sharing a name with a checkbox are skipped,
+/// since they only hold the value sent for an unchecked box.
+pub(crate) fn extract_hidden_fields(form: ElementRef) -> Vec<(String, String)> {
+    static HIDDEN_INPUT_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+        Selector::parse("input[type=\"hidden\"][name], input[type=\"submit\"][name][value]")
+            .expect("invalid HIDDEN_INPUT_SELECTOR")
+    });
+    static CHECKBOX_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+        Selector::parse("input[type=\"checkbox\"][name]").expect("invalid CHECKBOX_SELECTOR")
+    });
+
+    let checkbox_names: Vec<&str> = form
+        .select(&CHECKBOX_SELECTOR)
+        .filter_map(|input| input.value().attr("name"))
+        .collect();
+
+    form.select(&HIDDEN_INPUT_SELECTOR)
+        .filter_map(|input| {
+            let name = input.value().attr("name")?;
+            if checkbox_names.contains(&name) {
+                return None;
+            }
+            let value = input.value().attr("value").unwrap_or_default();
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Serialize hidden form fields, leaving out the authenticity token
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_hidden_fields<S>(
+    fields: &[(String, String)],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_seq(
+        fields
+            .iter()
+            .filter(|(name, _)| name != "authenticity_token"),
+    )
+}
+
+#[cfg(feature = "client")]
+/// Get a bounded excerpt of an html document, starting at the body if there is one
+pub(crate) fn html_excerpt(text: &str, max_len: usize) -> String {
+    let start = text.find("<body").unwrap_or(0);
+    let text = &text[start..];
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &text[..end])
+}
+
+#[cfg(feature = "client")]
+/// Dump a response body to a new file in the given directory, returning its path
+pub(crate) fn dump_body(dir: &Path, url: &Url, text: &str) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let name: String = url
+        .path()
+        .trim_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{timestamp}-{name}.html"));
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+#[cfg(all(test, feature = "client"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn html_excerpt_is_bounded() {
+        let text = "<html><head></head><body>ééééé</body></html>";
+        assert_eq!(html_excerpt(text, 100), "<body>ééééé</body></html>");
+        assert_eq!(html_excerpt(text, 8), "<body>é...");
+        assert_eq!(html_excerpt(text, 9), "<body>é...");
+    }
+}
@@ -0,0 +1,587 @@
+#![cfg(feature = "client")]
+
+use shift_client::{
+    client::{ExponentialBackoff, NoRetry, PollPolicy},
+    types::{ProfileUpdate, RedemptionOutcome, Service},
+    Client, ClientBuilder, ShiftCode, ShiftError,
+};
+use shift_fake_server::{CodeBehavior, FakeServer, Scenario};
+use std::time::Duration;
+use url::Url;
+
+const CODE: &str = "KJKBT-KCBJC-FXT65-TTTT3-W3XSR";
+
+async fn start(scenario: Scenario) -> (FakeServer, Client) {
+    let server = FakeServer::start(scenario)
+        .await
+        .expect("failed to start fake server");
+    let client = Client::builder("user@example.com".into(), "password".into())
+        .base_url(Url::parse(&server.url()).unwrap())
+        .retry_policy(NoRetry)
+        .poll_policy(PollPolicy {
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            ..PollPolicy::default()
+        })
+        .build()
+        .expect("failed to build client");
+    (server, client)
+}
+
+fn code() -> ShiftCode {
+    CODE.parse().unwrap()
+}
+
+#[tokio::test]
+async fn login() {
+    let (_server, client) = start(Scenario::new()).await;
+    let account = client.login().await.expect("failed to log in");
+    assert_eq!(account.email, "user@example.com");
+}
+
+#[tokio::test]
+async fn login_bad_password() {
+    let scenario = Scenario {
+        password: "other".into(),
+        ..Scenario::new()
+    };
+    let (_server, client) = start(scenario).await;
+    let error = client.login().await.unwrap_err();
+    assert!(
+        matches!(error, ShiftError::IncorrectEmailOrPassword),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn redeem_code() {
+    let scenario = Scenario::new()
+        .code(CODE, CodeBehavior::Redeemable)
+        .in_progress_polls(2);
+    let (server, client) = start(scenario).await;
+    client.login().await.unwrap();
+
+    let report = client.redeem_code(&code()).await.unwrap();
+    assert!(report.is_redeemed());
+    assert_eq!(report.entries.len(), 2);
+    assert!(report
+        .entries
+        .iter()
+        .all(|entry| entry.outcome == RedemptionOutcome::Redeemed));
+    // Each redemption is polled until it stops being in progress.
+    assert_eq!(server.request_count("/code_redemptions"), 2);
+
+    let report = client.redeem_code(&code()).await.unwrap();
+    assert!(report
+        .entries
+        .iter()
+        .all(|entry| entry.outcome == RedemptionOutcome::AlreadyRedeemed));
+}
+
+#[tokio::test]
+async fn rejected_codes() {
+    let scenario = Scenario::new()
+        .code(CODE, CodeBehavior::Expired)
+        .code(
+            "WSWTB-ZKS3W-ZZSZK-3BB3J-WBTRW",
+            CodeBehavior::LaunchGameRequired,
+        )
+        .code("C3WBB-RX6R5-WJTJT-JTJB3-B5KZ9", CodeBehavior::Failed);
+    let (_server, client) = start(scenario).await;
+    client.login().await.unwrap();
+
+    let report = client.redeem_code(&code()).await.unwrap();
+    assert_eq!(report.rejection, Some(RedemptionOutcome::Expired));
+
+    let report = client
+        .redeem_code(&"ZZZZZ-ZZZZZ-ZZZZZ-ZZZZZ-ZZZZZ".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(report.rejection, Some(RedemptionOutcome::NonExistent));
+
+    let report = client
+        .redeem_code(&"WSWTB-ZKS3W-ZZSZK-3BB3J-WBTRW".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(report.entries[0].outcome, RedemptionOutcome::LaunchGame);
+
+    let report = client
+        .redeem_code(&"C3WBB-RX6R5-WJTJT-JTJB3-B5KZ9".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(report.entries[0].outcome, RedemptionOutcome::Failed);
+}
+
+#[tokio::test]
+async fn failed_form_keeps_report() {
+    let scenario = Scenario::new()
+        .code(CODE, CodeBehavior::Redeemable)
+        .broken_service("epic");
+    let (_server, client) = start(scenario).await;
+    client.login().await.unwrap();
+
+    let report = client.redeem_code(&code()).await.unwrap();
+    let outcomes: Vec<_> = report
+        .entries
+        .iter()
+        .map(|entry| (entry.service.clone(), &entry.outcome))
+        .collect();
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(outcomes[0], (Service::Steam, &RedemptionOutcome::Redeemed));
+    assert_eq!(outcomes[1].0, Service::Epic);
+    assert!(matches!(outcomes[1].1, RedemptionOutcome::Error(_)));
+}
+
+#[tokio::test]
+async fn poll_timeout() {
+    let scenario = Scenario::new()
+        .code(CODE, CodeBehavior::Redeemable)
+        .in_progress_polls(u32::MAX);
+    let (_server, client) = start(scenario).await;
+    client.login().await.unwrap();
+
+    let report = client.redeem_code(&code()).await.unwrap();
+    assert_eq!(report.entries.len(), 2);
+    assert!(report
+        .entries
+        .iter()
+        .all(|entry| matches!(&entry.outcome, RedemptionOutcome::Error(error) if error.contains("timed out"))));
+}
+
+#[tokio::test]
+async fn rate_limited() {
+    let scenario = Scenario::new()
+        .code(CODE, CodeBehavior::Redeemable)
+        .rate_limited_requests(2);
+    let (server, client) = start(scenario.clone()).await;
+    client.login().await.unwrap();
+
+    let error = client.redeem_code(&code()).await.unwrap_err();
+    assert!(
+        matches!(
+            error,
+            ShiftError::RateLimited {
+                retry_after: Some(Duration::ZERO)
+            }
+        ),
+        "{error:?}"
+    );
+    drop(server);
+
+    let server = FakeServer::start(scenario).await.unwrap();
+    let client = Client::builder("user@example.com".into(), "password".into())
+        .base_url(Url::parse(&server.url()).unwrap())
+        .retry_policy(ExponentialBackoff {
+            base_delay: Duration::from_millis(1),
+            ..ExponentialBackoff::default()
+        })
+        .build()
+        .unwrap();
+    client.login().await.unwrap();
+
+    let report = client.redeem_code(&code()).await.unwrap();
+    assert!(report.is_redeemed());
+    assert_eq!(server.request_count("/entitlement_offer_codes"), 3);
+}
+
+#[tokio::test]
+async fn restored_session_expires() {
+    let (server, client) = start(Scenario::new()).await;
+    client.login().await.unwrap();
+    let session = client.session().unwrap();
+
+    let client = ClientBuilder::from_session(session)
+        .base_url(Url::parse(&server.url()).unwrap())
+        .build()
+        .unwrap();
+    let account = client.check_session().await.unwrap();
+    assert_eq!(account.email, "user@example.com");
+
+    server.expire_sessions();
+    let error = client.check_session().await.unwrap_err();
+    assert!(matches!(error, ShiftError::SessionExpired), "{error:?}");
+}
+
+#[tokio::test]
+async fn relogin_after_session_expiry() {
+    let scenario = Scenario::new().code(CODE, CodeBehavior::Redeemable);
+    let (server, client) = start(scenario).await;
+    client.login().await.unwrap();
+
+    server.expire_sessions();
+
+    let report = client.redeem_code(&code()).await.unwrap();
+    assert!(report.is_redeemed());
+    assert_eq!(server.request_count("/sessions"), 2);
+}
+
+#[tokio::test]
+async fn unprocessable_is_not_session_expiry() {
+    let scenario = Scenario::new().code(CODE, CodeBehavior::Unprocessable);
+    let (server, client) = start(scenario).await;
+    client.login().await.unwrap();
+
+    let error = client.redeem_code(&code()).await.unwrap_err();
+    assert!(
+        matches!(&error, ShiftError::Reqwest(error) if error.status() == Some(reqwest::StatusCode::UNPROCESSABLE_ENTITY)),
+        "{error:?}"
+    );
+    assert_eq!(server.request_count("/sessions"), 1);
+}
+
+#[tokio::test]
+async fn logout_stops_relogin() {
+    let scenario = Scenario::new().code(CODE, CodeBehavior::Redeemable);
+    let (server, client) = start(scenario).await;
+    client.login().await.unwrap();
+
+    client.logout().await.unwrap();
+    let error = client.redeem_code(&code()).await.unwrap_err();
+    assert!(matches!(error, ShiftError::SessionExpired), "{error:?}");
+    assert_eq!(server.request_count("/sessions"), 1);
+
+    // Logging in again turns relogin back on.
+    client.login().await.unwrap();
+    server.expire_sessions();
+    let report = client.redeem_code(&code()).await.unwrap();
+    assert!(report.is_redeemed());
+    assert_eq!(server.request_count("/sessions"), 3);
+}
+
+#[tokio::test]
+async fn update_profile() {
+    let (_server, client) = start(Scenario::new()).await;
+    client.login().await.unwrap();
+
+    let account = client
+        .update_profile(ProfileUpdate {
+            display_name: Some("NewDisplayName".into()),
+            ..ProfileUpdate::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(account.display_name, "NewDisplayName");
+    assert_eq!(account.first_name, "Fake");
+    assert_eq!(
+        client.check_session().await.unwrap().display_name,
+        "NewDisplayName"
+    );
+
+    let error = client
+        .update_profile(ProfileUpdate {
+            display_name: Some(" ".into()),
+            ..ProfileUpdate::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, ShiftError::ProfileUpdateFailed),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn change_password() {
+    let (server, client) = start(Scenario::new()).await;
+    client.login().await.unwrap();
+
+    let account = client
+        .change_password("password", "new-password")
+        .await
+        .unwrap();
+    assert_eq!(account.email, "user@example.com");
+
+    // Logging in again uses the new password.
+    server.expire_sessions();
+    client.login().await.unwrap();
+}
+
+#[tokio::test]
+async fn change_password_wrong_current_password() {
+    let (server, client) = start(Scenario::new()).await;
+    client.login().await.unwrap();
+
+    let error = client
+        .change_password("wrong", "new-password")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, ShiftError::PasswordChangeFailed),
+        "{error:?}"
+    );
+
+    // The old password is kept.
+    server.expire_sessions();
+    client.login().await.unwrap();
+}
+
+#[tokio::test]
+async fn set_preferences() {
+    let (_server, client) = start(Scenario::new()).await;
+    client.login().await.unwrap();
+
+    let mut page = client.get_preferences().await.unwrap();
+    assert_eq!(page.preferences.len(), 3);
+    assert!(page.preferences.iter().all(|preference| preference.enabled));
+
+    page.disable_all();
+    let page = client.set_preferences(&page).await.unwrap();
+    assert!(page
+        .preferences
+        .iter()
+        .all(|preference| !preference.enabled));
+
+    let mut page = client.get_preferences().await.unwrap();
+    assert!(page
+        .preferences
+        .iter()
+        .all(|preference| !preference.enabled));
+
+    assert!(page.set("user[email_preferences][shift_codes]", true));
+    let page = client.set_preferences(&page).await.unwrap();
+    assert!(
+        page.get("user[email_preferences][shift_codes]")
+            .unwrap()
+            .enabled
+    );
+    assert!(
+        !page
+            .get("user[email_preferences][newsletter]")
+            .unwrap()
+            .enabled
+    );
+}
+
+#[tokio::test]
+async fn set_preferences_not_kept() {
+    let (_server, client) = start(Scenario::new().locked_preference("newsletter")).await;
+    client.login().await.unwrap();
+
+    let mut page = client.get_preferences().await.unwrap();
+    page.disable_all();
+    let error = client.set_preferences(&page).await.unwrap_err();
+    assert!(
+        matches!(&error, ShiftError::PreferencesUpdateFailed { names } if names == &["user[email_preferences][newsletter]"]),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn record_and_replay() {
+    use shift_client::transport::{Recorder, Replayer};
+
+    let path =
+        std::env::temp_dir().join(format!("shift-client-cassette-{}.json", std::process::id()));
+    let scenario = Scenario {
+        password: "hunter2".into(),
+        ..Scenario::new()
+    }
+    .code(CODE, CodeBehavior::Redeemable);
+    let server = FakeServer::start(scenario)
+        .await
+        .expect("failed to start fake server");
+    let base_url = Url::parse(&server.url()).unwrap();
+
+    let client = Client::builder("user@example.com".into(), "hunter2".into())
+        .base_url(base_url.clone())
+        .poll_policy(PollPolicy {
+            initial_delay: Duration::from_millis(1),
+            ..PollPolicy::default()
+        })
+        .transport(Recorder::new(&path))
+        .build()
+        .unwrap();
+    client.login().await.unwrap();
+    let recorded = client.redeem_code(&code()).await.unwrap();
+    drop(server);
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("hunter2"));
+    assert!(!cassette.contains("user@example.com"));
+    assert!(!cassette.contains("home-csrf-token"));
+    assert!(!cassette.contains("csrf-0000"));
+    assert!(!cassette.contains("FakeDisplayName"));
+
+    let replayer = Replayer::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let client = Client::builder("user@example.com".into(), "hunter2".into())
+        .base_url(base_url)
+        .poll_policy(PollPolicy {
+            initial_delay: Duration::from_millis(1),
+            ..PollPolicy::default()
+        })
+        .transport(replayer)
+        .build()
+        .unwrap();
+    client.login().await.unwrap();
+    let replayed = client.redeem_code(&code()).await.unwrap();
+
+    assert!(replayed.is_redeemed());
+    assert_eq!(replayed.entries.len(), recorded.entries.len());
+}
+
+#[tokio::test]
+async fn account_pool() {
+    use shift_client::AccountPool;
+
+    let (_redeemable_server, redeemable) =
+        start(Scenario::new().code(CODE, CodeBehavior::Redeemable)).await;
+    let (_expired_server, expired) = start(Scenario::new().code(CODE, CodeBehavior::Expired)).await;
+    let (timeout_server, timeout) = start(
+        Scenario::new()
+            .code(CODE, CodeBehavior::Redeemable)
+            .in_progress_polls(u32::MAX),
+    )
+    .await;
+    for client in [&redeemable, &expired, &timeout] {
+        client.login().await.unwrap();
+    }
+
+    let pool: AccountPool = [redeemable, expired, timeout].into_iter().collect();
+    let pool = pool
+        .concurrency(2)
+        .min_code_interval(Duration::from_millis(1));
+    assert_eq!(pool.len(), 3);
+
+    let results = pool.redeem_code(&code()).await;
+    assert_eq!(results.len(), 3);
+    assert!(results
+        .iter()
+        .all(|result| result.email == "user@example.com"));
+    assert!(results[0].result.as_ref().unwrap().is_redeemed());
+    assert_eq!(
+        results[1].result.as_ref().unwrap().rejection,
+        Some(RedemptionOutcome::Expired)
+    );
+    let timed_out = results[2].result.as_ref().unwrap();
+    assert!(!timed_out.is_redeemed());
+    assert!(timed_out
+        .entries
+        .iter()
+        .all(|entry| matches!(entry.outcome, RedemptionOutcome::Error(_))));
+    assert!(timeout_server.request_count("/code_redemptions") > 0);
+}
+
+#[cfg(feature = "ledger")]
+#[tokio::test]
+async fn ledger_skips_final_outcomes() {
+    use shift_client::Ledger;
+
+    let scenario = Scenario::new()
+        .code(CODE, CodeBehavior::Redeemable)
+        .code("ZZZZZ-ZZZZZ-ZZZZZ-ZZZZZ-ZZZZZ", CodeBehavior::Expired);
+    let (server, client) = start(scenario).await;
+    client.login().await.unwrap();
+    let ledger = Ledger::in_memory();
+
+    let report = client
+        .redeem_code_with_ledger(&code(), &ledger)
+        .await
+        .unwrap()
+        .expect("code was skipped");
+    assert!(report.is_redeemed());
+    assert_eq!(ledger.entries().len(), 2);
+
+    let report = client
+        .redeem_code_with_ledger(&code(), &ledger)
+        .await
+        .unwrap();
+    assert!(report.is_none());
+    assert_eq!(server.request_count("/entitlement_offer_codes"), 1);
+
+    let expired: ShiftCode = "ZZZZZ-ZZZZZ-ZZZZZ-ZZZZZ-ZZZZZ".parse().unwrap();
+    let report = client
+        .redeem_code_with_ledger(&expired, &ledger)
+        .await
+        .unwrap()
+        .expect("code was skipped");
+    assert_eq!(report.rejection, Some(RedemptionOutcome::Expired));
+    assert!(client
+        .redeem_code_with_ledger(&expired, &ledger)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(server.request_count("/entitlement_offer_codes"), 2);
+}
+
+#[cfg(feature = "ledger")]
+#[tokio::test]
+async fn ledger_records_failed_forms() {
+    use shift_client::Ledger;
+
+    let scenario = Scenario::new()
+        .code(CODE, CodeBehavior::Redeemable)
+        .broken_service("epic");
+    let (server, client) = start(scenario).await;
+    client.login().await.unwrap();
+    let ledger = Ledger::in_memory();
+
+    client
+        .redeem_code_with_ledger(&code(), &ledger)
+        .await
+        .unwrap()
+        .expect("code was skipped");
+    let entries = ledger.entries();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].is_final());
+    assert!(!entries[1].is_final());
+
+    // Only the failed form is tried again.
+    let report = client
+        .redeem_code_with_ledger(&code(), &ledger)
+        .await
+        .unwrap()
+        .expect("code was skipped");
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(report.entries[0].service, Service::Epic);
+    assert!(matches!(
+        report.entries[0].outcome,
+        RedemptionOutcome::Error(_)
+    ));
+    assert_eq!(server.request_count("/code_redemptions"), 3);
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_client() {
+    // The blocking client must not run inside a runtime, so the server gets its own thread.
+    let (url_tx, url_rx) = std::sync::mpsc::channel();
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let server_thread = std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build tokio runtime");
+        runtime.block_on(async move {
+            let scenario = Scenario::new().code(CODE, CodeBehavior::Redeemable);
+            let server = FakeServer::start(scenario)
+                .await
+                .expect("failed to start fake server");
+            url_tx.send(server.url()).unwrap();
+            let _ = stop_rx.await;
+        });
+    });
+    let url = url_rx.recv().expect("fake server thread failed");
+
+    let client: shift_client::blocking::Client =
+        Client::builder("user@example.com".into(), "password".into())
+            .base_url(Url::parse(&url).unwrap())
+            .retry_policy(NoRetry)
+            .poll_policy(PollPolicy {
+                initial_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(1),
+                ..PollPolicy::default()
+            })
+            .build()
+            .expect("failed to build client")
+            .into();
+
+    let account = client.login().expect("failed to log in");
+    assert_eq!(account.email, "user@example.com");
+    let report = client.redeem_code(&code()).unwrap();
+    assert!(report.is_redeemed());
+
+    drop(client);
+    drop(stop_tx);
+    server_thread.join().unwrap();
+}